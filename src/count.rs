@@ -8,34 +8,48 @@
 * conjectured (2) counting the number of perfect lyndon words, relying on the
 * iterators of lyndon.rs
 *
+* All the counts are carried out over arbitrary-precision integers, as the
+* number of cycles explodes with the order of the graph and the size of the
+* alphabet.
+*
 **/
-use crate::lyndon::LyndonWord;
+use crate::lyndon::{FixedContentLyndonWordIter, is_lyndon_bracelet};
 use crate::math::{factorial, mobius, psi};
-use crate::perfect::is_perfect;
+use crate::perfect::{
+    ForbiddenKmers, PerfectLyndonWordIter, PerfectnessChecker, rotations_through,
+};
 use crate::words::Word;
 use reikna::totient::totient as phi;
 
+// When built with the "parallel" feature, the enumeration that backs the
+// formulas is multi-threaded.
+#[cfg(feature = "parallel")]
+use crate::parallel::par_count_cycles_only_enum as count_by_enum;
+#[cfg(not(feature = "parallel"))]
+use count_cycles_only_enum as count_by_enum;
+
 use divisors::get_divisors;
 use num::integer::binomial;
+use num::{BigInt, BigUint};
 
 // The count enum wrap the counting results to keep an eye on the way they where
 // generated.
 #[derive(Debug, PartialEq)]
 pub enum Count {
-    FromProvedFormula(u32),
-    FromConjecturedFormula(u32),
-    FromEnum(u32),
+    FromProvedFormula(BigUint),
+    FromConjecturedFormula(BigUint),
+    FromEnum(BigUint),
     NoFormula,
 }
 
 // This to_option method allows to use .unwrap() afterward. This enable to
 // retrieve the value, removing a lot of matching pain "en passant".
 impl Count {
-    pub fn to_option(&self) -> Option<u32> {
-        match *self {
-            Count::FromProvedFormula(x) => Some(x),
-            Count::FromConjecturedFormula(x) => Some(x),
-            Count::FromEnum(x) => Some(x),
+    pub fn to_option(&self) -> Option<BigUint> {
+        match self {
+            Count::FromProvedFormula(x) => Some(x.clone()),
+            Count::FromConjecturedFormula(x) => Some(x.clone()),
+            Count::FromEnum(x) => Some(x.clone()),
             Count::NoFormula => None,
         }
     }
@@ -45,15 +59,28 @@ impl Count {
 // compiled here.
 
 // Number of de Bruijn sequence
-fn nb_dbs(order: usize, sigma: u8) -> u32 {
-    let sigma_to_kminusone = u32::pow(sigma as u32, (order - 1) as u32);
-    let a = u32::pow(factorial((sigma - 1) as u32), sigma_to_kminusone);
-    let b = u32::pow(sigma as u32, sigma_to_kminusone - order as u32);
-    a * b
+// NOTE. Exponents are computed over u64, as sigma^(k-1) exceeds u32 already for
+// binary graphs of order 33. The unary graph has a single de Bruijn cycle (its
+// loop), while sigma^(k-1) >= k for larger alphabets.
+pub(crate) fn nb_dbs(order: usize, sigma: u8) -> Result<BigUint, String> {
+    if order == 0 || sigma == 0 {
+        return Err(format!(
+            "There are no de Bruijn sequences of order {} over {} letters",
+            order, sigma
+        ));
+    }
+    let sigma_to_kminusone = u64::checked_pow(sigma as u64, (order - 1) as u32)
+        .ok_or("The number of vertices of the de Bruijn graph should fit in 64 bits")?;
+    let a = num::pow::Pow::pow(factorial((sigma - 1) as u32), sigma_to_kminusone);
+    if sigma == 1 {
+        return Ok(a);
+    }
+    let b = num::pow::Pow::pow(BigUint::from(sigma), sigma_to_kminusone - order as u64);
+    Ok(a * b)
 }
 
 // Number of Lyndon words
-fn nb_lw(l: usize, sigma: u8) -> u32 {
+fn nb_lw(l: usize, sigma: u8) -> BigUint {
     let mut divisors_of_l = get_divisors(l);
 
     // FIXME (low priority). Get rid of the "divisors" crate to get rid of this
@@ -68,46 +95,79 @@ fn nb_lw(l: usize, sigma: u8) -> u32 {
         divisors_of_l.push(l);
     }
 
-    let mut sum = 0;
+    let mut sum = BigInt::from(0);
     for d in divisors_of_l {
-        let mobius_coeff = mobius(d) as i64;
+        let mobius_coeff = BigInt::from(mobius(d));
         let quotient = (l / d) as u32;
-        let power = i64::pow(sigma as i64, quotient);
+        let power = BigInt::from(sigma).pow(quotient);
         sum += mobius_coeff * power;
     }
-    (sum / l as i64) as u32
+    (sum / l).to_biguint().unwrap()
 }
 
 // Number of NON-perfect LW, when the length of the LW is "+2" compared to the
 // perfectness criterion
 // STATUS: proved
-fn nb_nplw_plustwo(k: usize, sigma: u8) -> u32 {
-    phi(k as u64 + 2) as u32 * binomial(sigma as u32, 2) as u32
+fn nb_nplw_plustwo(k: usize, sigma: u8) -> BigUint {
+    BigUint::from(phi(k as u64 + 2)) * binomial(BigUint::from(sigma), BigUint::from(2u32))
 }
 
 // Number of NON-perfect LW, when the length of the LW is "+2" compared to the
 // perfectness criterion
 // STATUS: conjectured
-fn nb_nplw_plusthree(k: usize, sigma: u8) -> u32 {
-    let s = sigma as u32;
-    psi(k as u64 + 3) as u32 * (s - 1) * s * s / 2 - s * (s - 1)
+fn nb_nplw_plusthree(k: usize, sigma: u8) -> BigUint {
+    let s = BigUint::from(sigma);
+    let s_minus_one = &s - 1u32;
+    BigUint::from(psi(k as u64 + 3)) * &s_minus_one * &s * &s / 2u32 - &s * &s_minus_one
 }
 
 // Counting functions
 
 // A counting function that only relies on enumeration of perfect Lyndon words,
-// leveraging Theorem [cite once stabilized] of the paper. First, Lyndon words
-// are collected. Then, they are filtered to only keep the perfect ones. The
-// size of the vector is finally returned.
+// leveraging Theorem [cite once stabilized] of the paper. Perfect Lyndon words
+// are generated on the fly, and counted without ever being collected.
 pub fn count_cycles_only_enum(length: usize, order: usize, sigma: u8) -> Count {
-    let mut lws = LyndonWord::new_smallest(length as usize, sigma - 1)
-        .iter(true) // fixed_length = true
-        .collect::<Vec<Word>>();
-    // if length <= order, all LW are perfect -> no need to check :)
-    if length > order {
-        lws.retain(|w| is_perfect(w, order))
-    }
-    Count::FromEnum(lws.len() as u32)
+    let nb_plw = PerfectLyndonWordIter::new(length, order, sigma).count();
+    Count::FromEnum(BigUint::from(nb_plw))
+}
+
+// Counting the cycles whose Lyndon word has a fixed content (see
+// enum_cycles_fixed_content), which is only done through enumeration.
+pub fn count_cycles_fixed_content(content: &[usize], order: usize) -> Count {
+    let mut checker = PerfectnessChecker::new(order, content.len() as u8);
+    let nb_plw = FixedContentLyndonWordIter::new(content)
+        .filter(|w| checker.is_perfect(w))
+        .count();
+    Count::FromEnum(BigUint::from(nb_plw))
+}
+
+// Counting the classes of cycles up to reversal (see enum_cycles_up_to_reversal),
+// which is only done through enumeration.
+pub fn count_cycles_up_to_reversal(length: usize, order: usize, sigma: u8) -> Count {
+    let nb_classes = PerfectLyndonWordIter::new(length, order, sigma)
+        .filter(is_lyndon_bracelet)
+        .count();
+    Count::FromEnum(BigUint::from(nb_classes))
+}
+
+// Counting the cycles of the subgraph where the forbidden vertices and edges
+// are removed (see enum_cycles_avoiding), which is only done through
+// enumeration.
+pub fn count_cycles_avoiding(
+    forbidden: &ForbiddenKmers,
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> Count {
+    let nb_plw = PerfectLyndonWordIter::avoiding(length, order, sigma, forbidden.clone()).count();
+    Count::FromEnum(BigUint::from(nb_plw))
+}
+
+// Counting the cycles going through a given vertex (see enum_cycles_through),
+// which is only done through enumeration.
+pub fn count_cycles_through(vertex: &Word, length: usize, order: usize, sigma: u8) -> Count {
+    let nb_cycles = rotations_through(vertex, length, order, sigma).count();
+    Count::FromEnum(BigUint::from(nb_cycles))
 }
 
 // A counting function primarily relying on formulas, giving hand to
@@ -123,15 +183,19 @@ pub fn count_cycles_with_formula(
         Count::FromProvedFormula(nb_lw(length, sigma))
     } else if length == order + 2 {
         Count::FromProvedFormula(nb_lw(length, sigma) - nb_nplw_plustwo(order, sigma))
+    } else if u64::checked_pow(sigma as u64, order as u32).is_some_and(|n| n == length as u64) {
+        // NOTE. Checked before the conjectured formula, that does not hold for
+        // de Bruijn cycles of order 1 (e.g. in dBG(1, 4)), and without
+        // overflowing, as the number of vertices exceeds 64 bits for large
+        // orders (e.g. in dBG(64, 2))
+        Count::FromProvedFormula(nb_dbs(order, sigma).unwrap())
     } else if length == order + 3 {
         Count::FromConjecturedFormula(nb_lw(length, sigma) - nb_nplw_plusthree(order, sigma))
-    } else if length == usize::pow(sigma as usize, order as u32) {
-        Count::FromProvedFormula(nb_dbs(order, sigma))
     } else {
         if only_formula {
             Count::NoFormula
         } else {
-            count_cycles_only_enum(length, order, sigma)
+            count_by_enum(length, order, sigma)
         }
     }
 }
@@ -148,27 +212,62 @@ pub fn count_cycles_with_formula(
 //
 //
 
+// A shorthand for the counts obtained through enumeration
+#[cfg(test)]
+fn from_enum(n: u32) -> Count {
+    Count::FromEnum(BigUint::from(n))
+}
+
 #[test]
 fn test_count_cycles_only_enum() {
-    assert_eq!(count_cycles_only_enum(1, 3, 2), Count::FromEnum(2));
-    assert_eq!(count_cycles_only_enum(2, 3, 2), Count::FromEnum(1));
-    assert_eq!(count_cycles_only_enum(3, 3, 2), Count::FromEnum(2));
-    assert_eq!(count_cycles_only_enum(4, 3, 2), Count::FromEnum(3));
-    assert_eq!(count_cycles_only_enum(5, 3, 2), Count::FromEnum(2));
-    assert_eq!(count_cycles_only_enum(6, 3, 2), Count::FromEnum(3));
-    assert_eq!(count_cycles_only_enum(7, 3, 2), Count::FromEnum(4));
-    assert_eq!(count_cycles_only_enum(8, 3, 2), Count::FromEnum(2));
-    assert_eq!(count_cycles_only_enum(9, 3, 2), Count::FromEnum(0));
+    assert_eq!(count_cycles_only_enum(1, 3, 2), from_enum(2));
+    assert_eq!(count_cycles_only_enum(2, 3, 2), from_enum(1));
+    assert_eq!(count_cycles_only_enum(3, 3, 2), from_enum(2));
+    assert_eq!(count_cycles_only_enum(4, 3, 2), from_enum(3));
+    assert_eq!(count_cycles_only_enum(5, 3, 2), from_enum(2));
+    assert_eq!(count_cycles_only_enum(6, 3, 2), from_enum(3));
+    assert_eq!(count_cycles_only_enum(7, 3, 2), from_enum(4));
+    assert_eq!(count_cycles_only_enum(8, 3, 2), from_enum(2));
+    assert_eq!(count_cycles_only_enum(9, 3, 2), from_enum(0));
 }
 
 #[test]
 fn test_nb_dbs() {
     // Right hand side derived from OEIS's A016031
-    assert_eq!(nb_dbs(2, 2), 1);
-    assert_eq!(nb_dbs(3, 2), 2);
-    assert_eq!(nb_dbs(4, 2), 16);
-    assert_eq!(nb_dbs(5, 2), 2048);
-    assert_eq!(nb_dbs(6, 2), 67108864);
+    assert_eq!(nb_dbs(2, 2).unwrap(), BigUint::from(1u32));
+    assert_eq!(nb_dbs(3, 2).unwrap(), BigUint::from(2u32));
+    assert_eq!(nb_dbs(4, 2).unwrap(), BigUint::from(16u32));
+    assert_eq!(nb_dbs(5, 2).unwrap(), BigUint::from(2048u32));
+    assert_eq!(nb_dbs(6, 2).unwrap(), BigUint::from(67108864u32));
+    assert_eq!(nb_dbs(1, 5).unwrap(), BigUint::from(24u32));
+    assert_eq!(nb_dbs(3, 1).unwrap(), BigUint::from(1u32));
+
+    // Invalid parameters
+    assert!(nb_dbs(0, 2).is_err());
+    assert!(nb_dbs(3, 0).is_err());
+    assert!(nb_dbs(65, 2).is_err());
+}
+
+#[test]
+fn test_count_de_bruijn_cycles_of_order_one() {
+    // In dBG(1, 4), cycles of length 4 are both de Bruijn cycles and of length
+    // order + 3: the proved formula applies, not the conjectured one
+    let count = count_cycles_with_formula(4, 1, 4, true);
+    assert_eq!(count, Count::FromProvedFormula(nb_dbs(1, 4).unwrap()));
+    assert_eq!(
+        count.to_option(),
+        count_cycles_only_enum(4, 1, 4).to_option()
+    );
+}
+
+#[test]
+fn test_count_cycles_of_large_order() {
+    // The number of vertices of dBG(64, 2) does not fit in 64 bits, which is
+    // not an issue for cycles of length order + 3
+    assert!(matches!(
+        count_cycles_with_formula(67, 64, 2, true),
+        Count::FromConjecturedFormula(_)
+    ));
 }
 
 #[test]
@@ -177,8 +276,8 @@ fn test_nb_lw() {
         1, 2, 1, 2, 3, 6, 9, 18, 30, 56, 99, 186, 335, 630, 1161, 2182, 4080, 7710, 14532, 27594,
         52377, 99858, 190557, 364722, 698870, 1342176,
     ];
-    for i in 1..a001037.len() {
-        assert_eq!(nb_lw(i, 2), a001037[i]);
+    for (i, &expected) in a001037.iter().enumerate().skip(1) {
+        assert_eq!(nb_lw(i, 2), BigUint::from(expected as u32));
     }
 }
 
@@ -189,86 +288,189 @@ fn test_count_cycles_with_formula() {
         count_cycles_with_formula(1, 3, 2, false)
             .to_option()
             .unwrap(),
-        2
+        BigUint::from(2u32)
     );
     assert_eq!(
         count_cycles_with_formula(2, 3, 2, false)
             .to_option()
             .unwrap(),
-        1
+        BigUint::from(1u32)
     );
     assert_eq!(
         count_cycles_with_formula(3, 3, 2, false)
             .to_option()
             .unwrap(),
-        2
+        BigUint::from(2u32)
     );
     assert_eq!(
         count_cycles_with_formula(4, 3, 2, false)
             .to_option()
             .unwrap(),
-        3
+        BigUint::from(3u32)
     );
     assert_eq!(
         count_cycles_with_formula(5, 3, 2, false)
             .to_option()
             .unwrap(),
-        2
+        BigUint::from(2u32)
     );
     assert_eq!(
         count_cycles_with_formula(6, 3, 2, false)
             .to_option()
             .unwrap(),
-        3
+        BigUint::from(3u32)
     );
     assert_eq!(
         count_cycles_with_formula(7, 3, 2, false)
             .to_option()
             .unwrap(),
-        4
+        BigUint::from(4u32)
     );
     assert_eq!(
         count_cycles_with_formula(8, 3, 2, false)
             .to_option()
             .unwrap(),
-        2
+        BigUint::from(2u32)
     );
     assert_eq!(
         count_cycles_with_formula(9, 3, 2, false)
             .to_option()
             .unwrap(),
-        0
+        BigUint::from(0u32)
     );
 
     // Only formula (stating Count::NoFormula when stucked)
     assert_eq!(
         count_cycles_with_formula(1, 3, 2, true).to_option(),
-        Some(2)
+        Some(BigUint::from(2u32))
     );
     assert_eq!(
         count_cycles_with_formula(2, 3, 2, true).to_option(),
-        Some(1)
+        Some(BigUint::from(1u32))
     );
     assert_eq!(
         count_cycles_with_formula(3, 3, 2, true).to_option(),
-        Some(2)
+        Some(BigUint::from(2u32))
     );
     assert_eq!(
         count_cycles_with_formula(4, 3, 2, true).to_option(),
-        Some(3)
+        Some(BigUint::from(3u32))
     );
     assert_eq!(
         count_cycles_with_formula(5, 3, 2, true).to_option(),
-        Some(2)
+        Some(BigUint::from(2u32))
     );
     assert_eq!(
         count_cycles_with_formula(6, 3, 2, true).to_option(),
-        Some(3)
+        Some(BigUint::from(3u32))
     );
     assert_eq!(count_cycles_with_formula(7, 3, 2, true).to_option(), None);
     assert_eq!(
         count_cycles_with_formula(8, 3, 2, true).to_option(),
-        Some(2)
+        Some(BigUint::from(2u32))
     );
     assert_eq!(count_cycles_with_formula(9, 3, 2, true).to_option(), None);
 }
+
+#[test]
+fn test_counts_beyond_machine_integers() {
+    // 2^57 de Bruijn sequences of order 7, and (2^64 - 2^32) / 64 Lyndon words
+    // of length 64, both overflowing u32
+    assert_eq!(nb_dbs(7, 2).unwrap(), BigUint::from(144115188075855872u64));
+    assert_eq!(nb_lw(64, 2), BigUint::from(288230376084602880u64));
+    assert_eq!(
+        count_cycles_with_formula(128, 7, 2, true),
+        Count::FromProvedFormula(BigUint::from(144115188075855872u64))
+    );
+
+    // 4^13 * 6^16 de Bruijn sequences of order 3 over a quaternary alphabet
+    assert_eq!(
+        nb_dbs(3, 4).unwrap(),
+        BigUint::from(4u32).pow(13) * BigUint::from(6u32).pow(16)
+    );
+}
+
+#[test]
+fn test_count_cycles_fixed_content() {
+    // Summing over all contents of length 7 over {0, 1, 2} gives back all the
+    // cycles of that length
+    let mut total = BigUint::ZERO;
+    for n_0 in 0..=7 {
+        for n_1 in 0..=7 - n_0 {
+            let content = [n_0, n_1, 7 - n_0 - n_1];
+            total += count_cycles_fixed_content(&content, 2).to_option().unwrap();
+        }
+    }
+    assert_eq!(Count::FromEnum(total), count_cycles_only_enum(7, 2, 3));
+
+    // Cycles of dBG(4, 2) of length 16 and density 8, that is de Bruijn cycles
+    assert_eq!(count_cycles_fixed_content(&[8, 8], 4), from_enum(16));
+    assert_eq!(count_cycles_fixed_content(&[9, 7], 4), from_enum(0));
+}
+
+#[test]
+fn test_count_cycles_up_to_reversal() {
+    // Lengths up to the order: all Lyndon words are perfect, and the classes are
+    // counted by Lyndon bracelets (OEIS's A001371)
+    assert_eq!(count_cycles_up_to_reversal(6, 6, 2), from_enum(8));
+
+    // De Bruijn cycles of dBG(3, 2) and dBG(4, 2). The two de Bruijn cycles of
+    // dBG(3, 2) are the reversal of one another.
+    assert_eq!(count_cycles_up_to_reversal(8, 3, 2), from_enum(1));
+    assert_eq!(count_cycles_up_to_reversal(16, 4, 2), from_enum(8));
+}
+
+#[test]
+fn test_count_cycles_through() {
+    // Each cycle of length l goes through l vertices
+    for (length, order, sigma) in [(2, 3, 2), (5, 3, 2), (8, 3, 2), (7, 2, 3), (11, 4, 2)] {
+        let mut total = BigUint::ZERO;
+        for vertex in crate::words::all_words(order, sigma) {
+            total += count_cycles_through(&vertex, length, order, sigma)
+                .to_option()
+                .unwrap();
+        }
+        let nb_cycles = count_cycles_only_enum(length, order, sigma).to_option();
+        assert_eq!(total, nb_cycles.unwrap() * length);
+    }
+
+    // Every de Bruijn cycle goes through every vertex
+    assert_eq!(
+        count_cycles_through(&vec![1, 0, 1, 1], 16, 4, 2),
+        count_cycles_with_formula(16, 4, 2, false)
+            .to_option()
+            .map(Count::FromEnum)
+            .unwrap()
+    );
+}
+
+#[test]
+fn test_count_cycles_avoiding() {
+    // Nothing forbidden
+    let forbidden = ForbiddenKmers::default();
+    assert_eq!(
+        count_cycles_avoiding(&forbidden, 11, 4, 2),
+        count_cycles_only_enum(11, 4, 2)
+    );
+
+    // Forbidding a vertex, or all of its incoming edges, is the same
+    let vertex = ForbiddenKmers::parse("0.1.1.0", 4, 2).unwrap();
+    let edges = ForbiddenKmers::parse("0.0.1.1.0\n1.0.1.1.0", 4, 2).unwrap();
+    for length in 1..=16 {
+        assert_eq!(
+            count_cycles_avoiding(&vertex, length, 4, 2),
+            count_cycles_avoiding(&edges, length, 4, 2)
+        );
+    }
+
+    // The homopolymer-free cycles of dBG(3, 2)
+    let forbidden = ForbiddenKmers::parse("0.0.0\n1.1.1", 3, 2).unwrap();
+    let counts = (1..=8)
+        .map(|l| {
+            count_cycles_avoiding(&forbidden, l, 3, 2)
+                .to_option()
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(counts, [0u32, 1, 2, 1, 0, 2, 0, 0].map(BigUint::from));
+}
@@ -0,0 +1,444 @@
+/**
+*
+* #### dbs.rs ####
+*
+* While nb_dbs counts the de Bruijn sequences of order k (i.e. the Hamiltonian
+* cycles of dBG(k, sigma)), one of them can be generated directly, without
+* enumerating all the cycles of length sigma^k. The classic constructions are
+* proposed here, that all output sequences starting with 0^k.
+* - granddaddy: the concatenation, in lexicographic order, of the Lyndon words
+*   whose length divides k [1], in O(1) amortized time per symbol
+* - prefer-largest (i.e. prefer-one, in the binary case): the greedy algorithm
+*   appending the largest symbol that does not repeat a k-mer [2]
+* - prefer-same: the greedy algorithm repeating the last symbol when possible,
+*   and otherwise appending the largest symbol that does not repeat a k-mer
+* - grandmama and cool-lex: (binary) successor rules, that compute the next
+*   symbol from the last k ones [3, 4]
+* - cycle-joining: the cycles of the pure cycling register (i.e. the necklaces)
+*   joined along a cycle-joining tree (see joining.rs)
+*
+* Greedy algorithms also run in O(1) time per symbol, at the expense of a
+* bitset of sigma^k bits. Successor rules test whether some word is a necklace
+* at each step: the necklaces are listed beforehand into a bitset of 2^k bits,
+* so that they run in O(1) amortized time per symbol too.
+*
+* For small parameters, all the de Bruijn sequences can also be listed, as the
+* Eulerian circuits of dBG(k-1, sigma), following the BEST theorem [5, 6]: each
+* circuit starting with the loop 0^k is given by a spanning arborescence
+* oriented towards 0^(k-1) (the last edge leaving each vertex) together with an
+* ordering of the other edges leaving each vertex. This avoids filtering the
+* Lyndon words of length sigma^k with is_perfect.
+*
+*
+* Source(s).
+* [1] "Necklaces of beads in k colors and k-ary de Bruijn sequences", Harold
+* FREDRICKSEN, Irving J. KESSLER and James MAIORANA (1978)
+* [2] "A problem in arrangements", Monroe H. MARTIN (1934)
+* [3] "Constructing de Bruijn sequences with co-lexicographic order: the k-ary
+* Grandmama sequence", Patrick B. DRAGON, Oscar I. HERNANDEZ, Joe SAWADA, Aaron
+* WILLIAMS and Dennis WONG (2018)
+* [4] "A surprisingly simple de Bruijn sequence construction", Joe SAWADA, Aaron
+* WILLIAMS and Dennis WONG (2016)
+* [5] "Circuits and trees in oriented linear graphs", Tatyana VAN
+* AARDENNE-EHRENFEST and Nicolaas G. DE BRUIJN (1951)
+* [6] "On unicursal paths in a network of degree 4", William T. TUTTE and
+* Cedric A. B. SMITH (1941)
+*
+**/
+use crate::joining::{join_cycles, pcr_cycles};
+use crate::lyndon::{LyndonWord, NecklaceIter};
+use crate::perfect::PerfectnessChecker;
+use crate::symmetry::permutations;
+use crate::words::{Letter, Word};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Construction {
+    Granddaddy,
+    PreferLargest,
+    PreferSame,
+    Grandmama,
+    CoolLex,
+    CycleJoining,
+}
+
+// A de Bruijn sequence of order k over [0..sigma), following the given
+// construction. Successor rules are only available over the binary alphabet.
+pub fn de_bruijn_sequence(
+    order: usize,
+    sigma: u8,
+    construction: Construction,
+) -> Result<Word, String> {
+    match construction {
+        Construction::Granddaddy => granddaddy_sequence(order, sigma),
+        Construction::PreferLargest => prefer_largest_sequence(order, sigma),
+        Construction::PreferSame => prefer_same_sequence(order, sigma),
+        Construction::CycleJoining => {
+            check_parameters(order, sigma)?;
+            join_cycles(&pcr_cycles(order, sigma), order, sigma)
+        }
+        Construction::Grandmama | Construction::CoolLex if sigma != 2 => Err(format!(
+            "The {:?} successor rule is only defined over the binary alphabet",
+            construction
+        )),
+        Construction::Grandmama => successor_rule_sequence(order, grandmama_tested_word),
+        Construction::CoolLex => successor_rule_sequence(order, cool_lex_tested_word),
+    }
+}
+
+// The constructions require a positive order and a non-empty alphabet
+fn check_parameters(order: usize, sigma: u8) -> Result<(), String> {
+    match order == 0 || sigma == 0 {
+        true => Err("The order and the alphabet should be non-empty".to_string()),
+        false => Ok(()),
+    }
+}
+
+// The FKM construction, relying on the bounded-length iterator on Lyndon words
+pub fn granddaddy_sequence(order: usize, sigma: u8) -> Result<Word, String> {
+    let mut sequence = Vec::with_capacity(nb_kmers(order, sigma)?);
+    let mut lw = LyndonWord::new_smallest(order, sigma - 1);
+    for w in lw.iter(false) {
+        if order.is_multiple_of(w.len()) {
+            sequence.extend(w);
+        }
+    }
+    Ok(sequence)
+}
+
+// The number sigma^k of k-mers, i.e. the length of the de Bruijn sequences, or
+// an error if the parameters are invalid or if it does not fit in a usize
+fn nb_kmers(order: usize, sigma: u8) -> Result<usize, String> {
+    check_parameters(order, sigma)?;
+    usize::checked_pow(sigma as usize, order as u32).ok_or(format!(
+        "The de Bruijn sequences of order {} over {} letters are too long",
+        order, sigma
+    ))
+}
+
+// The greedy construction: starting from the seed, the first symbol (in the
+// order of preference) that yields a new k-mer is appended, until none does.
+// The k-mers are encoded in base sigma to be stored in a bitset. When the
+// construction succeeds, the linear sequence contains all the k-mers, and ends
+// with its seed, so that it is folded into a cyclic sequence. As the seed and
+// the preference may get the construction stuck, the result is checked, and an
+// error is returned if it is not a de Bruijn sequence.
+fn greedy_sequence(
+    order: usize,
+    sigma: u8,
+    seed: &Word,
+    preference: impl Fn(Letter) -> Vec<Letter>,
+) -> Result<Word, String> {
+    let nb_kmers = nb_kmers(order, sigma)?;
+    let top = nb_kmers / sigma as usize;
+    let mut seen = vec![false; nb_kmers];
+    let mut sequence = seed.clone();
+    let mut code = seed
+        .iter()
+        .fold(0, |code, &a| code * sigma as usize + a as usize);
+    loop {
+        let last = sequence.last().copied().unwrap_or(0);
+        let next = preference(last).into_iter().find(|&a| {
+            let next_code = (code % top) * sigma as usize + a as usize;
+            !seen[next_code]
+        });
+        let Some(a) = next else {
+            break;
+        };
+        code = (code % top) * sigma as usize + a as usize;
+        seen[code] = true;
+        sequence.push(a);
+    }
+    sequence.truncate(nb_kmers);
+    if !PerfectnessChecker::new(order, sigma).is_perfect(&sequence) {
+        return Err("The greedy construction got stuck before covering all the k-mers".to_string());
+    }
+    Ok(rotate_to_zeros(sequence, order))
+}
+
+pub fn prefer_largest_sequence(order: usize, sigma: u8) -> Result<Word, String> {
+    check_parameters(order, sigma)?;
+    greedy_sequence(order, sigma, &vec![0; order - 1], |_| {
+        (0..sigma).rev().collect()
+    })
+}
+
+// NOTE. Seeding with 0^(k-1) would get the construction stuck early, so that the
+// alternating seed 0101... is used instead.
+pub fn prefer_same_sequence(order: usize, sigma: u8) -> Result<Word, String> {
+    check_parameters(order, sigma)?;
+    let seed = (0..order - 1).map(|i| (i % 2) as Letter).collect::<Word>();
+    let seed = seed.into_iter().map(|a| a.min(sigma - 1)).collect();
+    greedy_sequence(order, sigma, &seed, |last| {
+        let others = (0..sigma).rev().filter(move |&a| a != last);
+        std::iter::once(last).chain(others).collect()
+    })
+}
+
+// Rotate a cyclic sequence so that it starts with 0^k
+fn rotate_to_zeros(mut sequence: Word, order: usize) -> Word {
+    let n = sequence.len();
+    if let Some(i) = (0..n).find(|&i| (0..order).all(|j| sequence[(i + j) % n] == 0)) {
+        sequence.rotate_left(i);
+    }
+    sequence
+}
+
+// The binary necklaces of length k, as a bitset indexed by their code (most
+// significant bit first). FKM lists the O(2^k / k) prenecklaces in O(k) time
+// each, hence O(2^k) time overall.
+fn necklace_bitset(order: usize) -> Vec<bool> {
+    let mut is_necklace = vec![false; 1 << order];
+    for necklace in NecklaceIter::necklaces(order, 1, true) {
+        let code = necklace.iter().fold(0, |code, &a| code << 1 | a as usize);
+        is_necklace[code] = true;
+    }
+    is_necklace
+}
+
+// The binary sequence obtained by iterating a successor rule from 0^k. States
+// a_1...a_k are identified with their code, and both rules complement a_1 iff
+// some word is a necklace, whose code is given by tested_word(state, k). The
+// necklaces being listed beforehand, each symbol takes O(1) amortized time.
+fn successor_rule_sequence(
+    order: usize,
+    tested_word: impl Fn(usize, usize) -> usize,
+) -> Result<Word, String> {
+    let n = nb_kmers(order, 2)?;
+    let is_necklace = necklace_bitset(order);
+    let mut state = 0;
+    let mut sequence = Vec::with_capacity(n);
+    for _ in 0..n {
+        let first = state >> (order - 1);
+        sequence.push(first as Letter);
+        let next = first ^ is_necklace[tested_word(state, order)] as usize;
+        state = (state << 1 | next) & (n - 1);
+    }
+    Ok(sequence)
+}
+
+// The cool-lex successor rule of [4]: the word tested is a_2...a_k 1
+fn cool_lex_tested_word(state: usize, order: usize) -> usize {
+    (state << 1 | 1) & ((1 << order) - 1)
+}
+
+// The grandmama successor rule: writing a_2...a_k = b 0^t, where b is empty or
+// ends with 1, the word tested is 0^t 1 b
+fn grandmama_tested_word(state: usize, order: usize) -> usize {
+    let tail = state & ((1 << (order - 1)) - 1);
+    let t = (tail.trailing_zeros() as usize).min(order - 1);
+    1 << (order - 1 - t) | tail >> t
+}
+
+// The spanning arborescences of dBG(k-1, sigma) oriented towards its root
+// 0^(k-1), where vertices are identified with their code in base sigma. Each
+// of them is given by the letter of the edge leaving every vertex (the root
+// has none, and is given 0). Letters are assigned vertex after vertex, and
+// backtracked as soon as they close a cycle.
+fn arborescences(order: usize, sigma: u8) -> Vec<Vec<Letter>> {
+    let n = usize::pow(sigma as usize, order as u32 - 1);
+    let successor = |u: usize, a: Letter| (u * sigma as usize + a as usize) % n;
+
+    fn place(
+        v: usize,
+        parent: &mut Vec<Option<Letter>>,
+        successor: &dyn Fn(usize, Letter) -> usize,
+        sigma: u8,
+        arborescences: &mut Vec<Vec<Letter>>,
+    ) {
+        if v == parent.len() {
+            arborescences.push(parent.iter().map(|a| a.unwrap_or(0)).collect());
+            return;
+        }
+        for a in 0..sigma {
+            // Follow the edges already chosen from the successor of v, until
+            // the root or a vertex with no edge yet is met
+            let mut u = successor(v, a);
+            while u != 0 && u != v {
+                match parent[u] {
+                    Some(b) => u = successor(u, b),
+                    None => break,
+                }
+            }
+            if u == v {
+                continue;
+            }
+            parent[v] = Some(a);
+            place(v + 1, parent, successor, sigma, arborescences);
+            parent[v] = None;
+        }
+    }
+
+    let mut arborescences = Vec::new();
+    place(1, &mut vec![None; n], &successor, sigma, &mut arborescences);
+    arborescences
+}
+
+// All the de Bruijn sequences of order k over [0..sigma), starting with 0^k and
+// sorted lexicographically, as Eulerian circuits of dBG(k-1, sigma). There are
+// nb_dbs of them, so that this is only tractable for small parameters.
+pub fn enum_de_bruijn_sequences(order: usize, sigma: u8) -> Vec<Word> {
+    if order == 0 || sigma == 0 {
+        return Vec::new();
+    }
+    let n = usize::pow(sigma as usize, order as u32 - 1);
+    let orderings = permutations(sigma - 1);
+
+    let mut sequences = Vec::new();
+    for tree in arborescences(order, sigma) {
+        // The orderings of the non-tree edges of all vertices are enumerated as
+        // the digits of a counter in base (sigma - 1)!
+        let mut digits = vec![0; n];
+        loop {
+            // The edges leaving each vertex, in the order they are used: the
+            // loop 0^k first for the root, and the tree edge last for others
+            let edges = (0..n)
+                .map(|v| {
+                    let others = (0..sigma).filter(|&a| a != tree[v]).collect::<Vec<_>>();
+                    let others = orderings[digits[v]].iter().map(|&i| others[i as usize]);
+                    match v {
+                        0 => std::iter::once(0).chain(others).collect::<Vec<_>>(),
+                        _ => others.chain(std::iter::once(tree[v])).collect(),
+                    }
+                })
+                .collect::<Vec<_>>();
+
+            // Walk the circuit from the root, the k-1 last letters spelling the
+            // root back, so that they are moved in front of the loop 0^k
+            let mut next = vec![0; n];
+            let mut sequence = Vec::with_capacity(n * sigma as usize);
+            let mut v = 0;
+            for _ in 0..n * sigma as usize {
+                let a = edges[v][next[v]];
+                next[v] += 1;
+                sequence.push(a);
+                v = (v * sigma as usize + a as usize) % n;
+            }
+            sequence.rotate_right(order - 1);
+            sequences.push(sequence);
+
+            let Some(v) = (0..n).find(|&v| digits[v] + 1 < orderings.len()) else {
+                break;
+            };
+            digits[..v].fill(0);
+            digits[v] += 1;
+        }
+    }
+    sequences.sort();
+    sequences
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_de_bruijn_sequence() {
+    let constructions = [
+        Construction::Granddaddy,
+        Construction::PreferLargest,
+        Construction::PreferSame,
+        Construction::Grandmama,
+        Construction::CoolLex,
+        Construction::CycleJoining,
+    ];
+    for construction in constructions {
+        for (max_order, sigma) in [(12, 2), (6, 3), (4, 4), (3, 5)] {
+            for order in 1..=max_order {
+                let Ok(sequence) = de_bruijn_sequence(order, sigma, construction) else {
+                    assert!(sigma != 2);
+                    continue;
+                };
+                assert_eq!(sequence.len(), usize::pow(sigma as usize, order as u32));
+                assert!(sequence[..order].iter().all(|&a| a == 0));
+                assert!(PerfectnessChecker::new(order, sigma).is_perfect(&sequence));
+            }
+        }
+    }
+
+    // The classic sequences of order 4
+    let sequence = |construction| de_bruijn_sequence(4, 2, construction).unwrap();
+    assert_eq!(
+        sequence(Construction::Granddaddy),
+        [0, 0, 0, 0, 1, 0, 0, 1, 1, 0, 1, 0, 1, 1, 1, 1]
+    );
+    assert_eq!(
+        sequence(Construction::PreferLargest),
+        [0, 0, 0, 0, 1, 1, 1, 1, 0, 1, 1, 0, 0, 1, 0, 1]
+    );
+    assert!(de_bruijn_sequence(4, 3, Construction::CoolLex).is_err());
+    assert!(de_bruijn_sequence(64, 2, Construction::Grandmama).is_err());
+    for (order, sigma) in [(0, 2), (3, 0)] {
+        assert!(granddaddy_sequence(order, sigma).is_err());
+        assert!(prefer_largest_sequence(order, sigma).is_err());
+        assert!(prefer_same_sequence(order, sigma).is_err());
+        assert!(de_bruijn_sequence(order, sigma, Construction::CycleJoining).is_err());
+    }
+    assert!(successor_rule_sequence(0, cool_lex_tested_word).is_err());
+
+    // The necklace bitset, against the least rotation
+    let is_necklace = necklace_bitset(8);
+    for (code, word) in crate::words::all_words(8, 2).into_iter().enumerate() {
+        let is_least_rotation = crate::lyndon::least_rotation(&word) == 0;
+        assert_eq!(is_necklace[code], is_least_rotation);
+    }
+
+    // A greedy construction that gets stuck, preferring 0 after the seed 0^k
+    let stuck = greedy_sequence(3, 2, &vec![0; 3], |_| vec![0, 1]);
+    assert!(stuck.is_err());
+
+    let sequence_of = |order| de_bruijn_sequence(order, 2, Construction::Grandmama).unwrap();
+
+    // The grandmama sequence is the concatenation of the aperiodic prefixes of
+    // necklaces, in co-lexicographic order
+    for order in 1..=10 {
+        let mut necklaces =
+            crate::lyndon::NecklaceIter::necklaces(order, 1, true).collect::<Vec<_>>();
+        necklaces.sort_by(|x, y| x.iter().rev().cmp(y.iter().rev()));
+        let mut sequence = Vec::new();
+        for necklace in necklaces {
+            let p = (1..=order).find(|&p| (p..order).all(|i| necklace[i] == necklace[i - p]));
+            sequence.extend(&necklace[..p.unwrap()]);
+        }
+        let sequence = rotate_to_zeros(sequence, order);
+        assert_eq!(sequence, sequence_of(order));
+    }
+}
+
+#[test]
+fn test_enum_de_bruijn_sequences() {
+    use crate::count::nb_dbs;
+    use num::BigUint;
+
+    // In dBG(2, 2), 1.1 and 1.0 have to leave through 1.0 and 0.0, while 0.1
+    // can go to either 1.0 or 1.1
+    assert_eq!(arborescences(3, 2), [[0, 0, 0, 0], [0, 1, 0, 0]]);
+
+    for (max_order, sigma) in [(5, 2), (3, 3), (2, 4), (1, 5)] {
+        for order in 1..=max_order {
+            let sequences = enum_de_bruijn_sequences(order, sigma);
+            assert_eq!(
+                BigUint::from(sequences.len()),
+                nb_dbs(order, sigma).unwrap()
+            );
+
+            // They are distinct de Bruijn sequences, among which the ones of
+            // the classic constructions
+            let mut checker = PerfectnessChecker::new(order, sigma);
+            assert!(sequences.iter().all(|s| checker.is_perfect(s)));
+            assert!(sequences.iter().all(|s| s[..order].iter().all(|&a| a == 0)));
+            assert!(sequences.windows(2).all(|w| w[0] < w[1]));
+            for construction in [Construction::Granddaddy, Construction::PreferSame] {
+                let sequence = de_bruijn_sequence(order, sigma, construction).unwrap();
+                assert!(sequences.binary_search(&sequence).is_ok());
+            }
+        }
+    }
+}
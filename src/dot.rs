@@ -0,0 +1,120 @@
+/**
+*
+* #### dot.rs ####
+*
+* Export of the de Bruijn graph to the DOT language of Graphviz, so that
+* pictures such as dbg32.png can be drawn automatically, e.g. with
+*   dbg_cycles export --order 3 | dot -Tpng > dbg32.png
+*
+* Vertices are labeled the same way the CLI prints them (0.1.1). Some cycles
+* can be highlighted: each of them is given its own color, and an edge shared by
+* several highlighted cycles is drawn with all their colors side by side.
+*
+**/
+use crate::words::{Cycle, Word, all_words};
+
+// Colors given to highlighted cycles, in order (and cyclically if there are
+// more cycles than colors)
+const PALETTE: [&str; 8] = [
+    "red",
+    "blue",
+    "forestgreen",
+    "darkorange",
+    "purple",
+    "cyan3",
+    "magenta",
+    "gold3",
+];
+
+// The label of a vertex, with dot-separated letters
+pub fn word_label(word: &Word) -> String {
+    let letters = word.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+    letters.join(".")
+}
+
+pub fn dbg_to_dot(order: usize, sigma: u8, cycles: &[Cycle]) -> String {
+    let vertices = all_words(order, sigma);
+
+    let mut dot = String::new();
+    dot.push_str("digraph dBG {\n");
+    dot.push_str(&format!("  label=\"dBG({}, {})\";\n", order, sigma));
+    dot.push_str("  node [shape=ellipse];\n");
+    for u in &vertices {
+        dot.push_str(&format!("  \"{}\";\n", word_label(u)));
+    }
+
+    for u in &vertices {
+        for a in 0..sigma {
+            let mut v = u[1..].to_vec();
+            v.push(a);
+
+            // Colors of the highlighted cycles going through the edge (u, v)
+            let colors = cycles
+                .iter()
+                .enumerate()
+                .filter(|(_, cycle)| cycle.windows(2).any(|e| e[0] == *u && e[1] == v))
+                .map(|(i, _)| PALETTE[i % PALETTE.len()])
+                .collect::<Vec<_>>();
+
+            dot.push_str(&format!(
+                "  \"{}\" -> \"{}\"",
+                word_label(u),
+                word_label(&v)
+            ));
+            if !colors.is_empty() {
+                dot.push_str(&format!(" [color=\"{}\", penwidth=2.5]", colors.join(":")));
+            }
+            dot.push_str(";\n");
+        }
+    }
+    dot.push_str("}\n");
+
+    dot
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_dbg_to_dot() {
+    let dot = dbg_to_dot(2, 2, &[]);
+    assert_eq!(
+        dot,
+        r#"digraph dBG {
+  label="dBG(2, 2)";
+  node [shape=ellipse];
+  "0.0";
+  "0.1";
+  "1.0";
+  "1.1";
+  "0.0" -> "0.0";
+  "0.0" -> "0.1";
+  "0.1" -> "1.0";
+  "0.1" -> "1.1";
+  "1.0" -> "0.0";
+  "1.0" -> "0.1";
+  "1.1" -> "1.0";
+  "1.1" -> "1.1";
+}
+"#
+    );
+
+    // The cycles of length 3 and 2 of dBG(2, 2), that share the edge 0.1 -> 1.0
+    let mut cycles = crate::r#enum::enum_cycles_fixed_length(3, 2, 2);
+    cycles.truncate(1);
+    cycles.extend(crate::r#enum::enum_cycles_fixed_length(2, 2, 2));
+    let dot = dbg_to_dot(2, 2, &cycles);
+    assert!(dot.contains("\"0.0\" -> \"0.1\" [color=\"red\", penwidth=2.5];"));
+    assert!(dot.contains("\"0.1\" -> \"1.0\" [color=\"red:blue\", penwidth=2.5];"));
+    assert!(dot.contains("\"1.0\" -> \"0.1\" [color=\"blue\", penwidth=2.5];"));
+    assert!(dot.contains("\"1.1\" -> \"1.0\";"));
+}
@@ -7,15 +7,19 @@
 * the perfect ones, and mapped to cycles.
 *
 **/
-use crate::lyndon::LyndonWord;
-use crate::perfect::{is_perfect, is_perfect_lyndon};
+use crate::lyndon::{
+    FixedContentLyndonWordIter, LyndonWord, conjugacy_class_representative, is_lyndon_bracelet,
+};
+use crate::perfect::{
+    ForbiddenKmers, PerfectLyndonWordIter, PerfectnessChecker, rotations_through,
+};
 use crate::words::{Cycle, Word};
 
 // In the paper, we presented to distinct bijective map, depending on the regime
 // of parameter. While not surprising (we distinguish the two regimes to ease
 // the presentation), these maps readily coincide on their implementation. The
 // following function thus accounts for f and g.
-fn map_word_to_cycle(w: Word, k: usize) -> Vec<Word> {
+pub(crate) fn map_word_to_cycle(w: Word, k: usize) -> Vec<Word> {
     let mut cycle = <Vec<Word>>::new();
     for i in 0..=w.len() {
         // First letter of the rotation of lw to consider
@@ -30,45 +34,162 @@ fn map_word_to_cycle(w: Word, k: usize) -> Vec<Word> {
     cycle
 }
 
-// The two distinct iterators on Lyndon words are here extended with perfect
-// filtering and the previously defined map. Note that the filtering strategy
-// slightly vary between the cases (the "length>order" switch is either present
-// at this level, or hidden in "is_perfect_lyndon").
+// The inverse map, that recovers the perfect Lyndon word encoding a cycle. The
+// cycle is given as its sequence of vertices, starting anywhere along the
+// cycle, and either closed (first vertex repeated at the end, as output by the
+// enumeration) or not. An error is returned whenever the sequence is not a
+// simple cycle of the de Bruijn graph.
+//
+// Reading the first letter of each vertex yields a rotation of the word; it is
+// primitive (otherwise, some vertex would be repeated), so that its least
+// rotation is the sought Lyndon word.
+pub fn map_cycle_to_word(cycle: &Cycle) -> Result<Word, String> {
+    let mut nodes = &cycle[..];
+    if nodes.len() > 1 && nodes.first() == nodes.last() {
+        nodes = &nodes[..nodes.len() - 1];
+    }
+    if nodes.is_empty() {
+        return Err("The cycle contains no vertex".to_string());
+    }
 
-pub fn enum_cycles_fixed_length(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
-    let mut collection = <Vec<Cycle>>::new();
-
-    // Recover LyndonWords
-    let mut lw = LyndonWord::new_smallest(length as usize, sigma - 1)
-        .iter(true)
-        .collect::<Vec<Word>>();
-    // Filter perfect only
-    if length > order {
-        lw.retain(|w| is_perfect(w, order))
+    let k = nodes[0].len();
+    if k == 0 || nodes.iter().any(|node| node.len() != k) {
+        return Err("The vertices are not non-empty words of the same length".to_string());
     }
-    // Map to cycles
-    for plw in lw {
-        collection.push(map_word_to_cycle(plw, order));
+
+    for (i, node) in nodes.iter().enumerate() {
+        let next = &nodes[(i + 1) % nodes.len()];
+        if node[1..] != next[..k - 1] {
+            return Err(format!("There is no edge from {:?} to {:?}", node, next));
+        }
     }
 
-    collection
+    let mut sorted_nodes = nodes.to_vec();
+    sorted_nodes.sort();
+    sorted_nodes.dedup();
+    if sorted_nodes.len() != nodes.len() {
+        return Err("The cycle goes through some vertex twice".to_string());
+    }
+
+    let word = nodes.iter().map(|node| node[0]).collect::<Word>();
+    Ok(conjugacy_class_representative(&word))
+}
+
+// The bounded-length iterator on Lyndon words is here extended with perfect
+// filtering and the previously defined map. Perfectness is checked with a
+// PerfectnessChecker, whose scratch buffer is shared by all the words. In the
+// fixed-length case, perfect Lyndon words are directly generated, with no
+// filtering needed.
+//
+// The iter_* functions are lazy: Lyndon words are generated, filtered and
+// mapped on the fly, so that cycles can be processed in constant memory. The
+// enum_* functions simply collect them.
+
+pub fn iter_cycles_fixed_length(
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Cycle> {
+    PerfectLyndonWordIter::new(length, order, sigma).map(move |plw| map_word_to_cycle(plw, order))
+}
+
+pub fn iter_cycles_bounded_length(
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Cycle> {
+    let mut checker = PerfectnessChecker::new(order, sigma);
+    LyndonWord::new_smallest(length, sigma - 1)
+        .into_iter(false)
+        .filter(move |w| checker.is_perfect_lyndon(w))
+        .map(move |plw| map_word_to_cycle(plw, order))
+}
+
+pub fn enum_cycles_fixed_length(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
+    iter_cycles_fixed_length(length, order, sigma).collect()
 }
 
 pub fn enum_cycles_bounded_length(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
-    let mut collection = <Vec<Cycle>>::new();
-
-    // Recover LyndonWords
-    let mut lw = LyndonWord::new_smallest(length as usize, sigma - 1)
-        .iter(false)
-        .collect::<Vec<Word>>();
-    // Filter perfect only
-    lw.retain(|w| is_perfect_lyndon(w, order));
-    // Map to cycles
-    for plw in lw {
-        collection.push(map_word_to_cycle(plw, order));
-    }
+    iter_cycles_bounded_length(length, order, sigma).collect()
+}
+
+// The cycles whose Lyndon word has a fixed content, i.e. a prescribed number of
+// occurrences of each letter. The content [n_0, n_1, ...] sets both the length
+// of the cycles and the alphabet [0..content.len()).
+pub fn iter_cycles_fixed_content(content: &[usize], order: usize) -> impl Iterator<Item = Cycle> {
+    let mut checker = PerfectnessChecker::new(order, content.len() as u8);
+    FixedContentLyndonWordIter::new(content)
+        .filter(move |w| checker.is_perfect(w))
+        .map(move |plw| map_word_to_cycle(plw, order))
+}
+
+pub fn enum_cycles_fixed_content(content: &[usize], order: usize) -> Vec<Cycle> {
+    iter_cycles_fixed_content(content, order).collect()
+}
+
+// The cycles of fixed length, up to reversal. Reversing the traversal of a
+// cycle yields a cycle of the reversed de Bruijn graph, which is isomorphic to
+// the de Bruijn graph itself through the reversal of vertices. The cycle of
+// word w is hence identified with the cycle of the reversal of w (which is
+// perfect as well). One cycle is kept per class: the one whose Lyndon word is a
+// Lyndon bracelet.
+pub fn iter_cycles_up_to_reversal(
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Cycle> {
+    PerfectLyndonWordIter::new(length, order, sigma)
+        .filter(is_lyndon_bracelet)
+        .map(move |plw| map_word_to_cycle(plw, order))
+}
+
+pub fn enum_cycles_up_to_reversal(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
+    iter_cycles_up_to_reversal(length, order, sigma).collect()
+}
+
+// The cycles of fixed length going through a given vertex. Only the words of
+// these cycles are generated, which is much faster than filtering all the
+// cycles when the length is large enough.
+//
+// The iter_ function yields cycles in the order of their rotations starting at
+// the vertex, while the enum_ function sorts them as the other enum_ functions
+// (i.e. in the order of their Lyndon words).
+pub fn iter_cycles_through(
+    vertex: &Word,
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Cycle> {
+    rotations_through(vertex, length, order, sigma)
+        .map(move |w| map_word_to_cycle(conjugacy_class_representative(&w), order))
+}
+
+// The cycles of fixed length of the subgraph of the de Bruijn graph where the
+// forbidden vertices and edges are removed. The generation of perfect Lyndon
+// words is pruned as soon as a forbidden k-mer or (k+1)-mer is read.
+pub fn iter_cycles_avoiding(
+    forbidden: &ForbiddenKmers,
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Cycle> {
+    PerfectLyndonWordIter::avoiding(length, order, sigma, forbidden.clone())
+        .map(move |plw| map_word_to_cycle(plw, order))
+}
+
+pub fn enum_cycles_avoiding(
+    forbidden: &ForbiddenKmers,
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> Vec<Cycle> {
+    iter_cycles_avoiding(forbidden, length, order, sigma).collect()
+}
 
-    collection
+pub fn enum_cycles_through(vertex: &Word, length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
+    let mut cycles = iter_cycles_through(vertex, length, order, sigma).collect::<Vec<_>>();
+    cycles.sort();
+    cycles
 }
 
 //
@@ -481,3 +602,178 @@ fn test_enum_all_cycles() {
     computed_cycles.sort_by_key(|x| (x.len(), x.clone()));
     assert_eq!(cycles_3_2, computed_cycles);
 }
+
+#[test]
+fn test_iter_cycles() {
+    assert!(iter_cycles_fixed_length(7, 3, 2).eq(enum_cycles_fixed_length(7, 3, 2)));
+    assert!(iter_cycles_bounded_length(9, 3, 2).eq(enum_cycles_bounded_length(9, 3, 2)));
+
+    // Cycles can be consumed without the whole collection being materialized
+    let mut cycles = iter_cycles_fixed_length(6, 5, 2);
+    assert_eq!(
+        cycles.next(),
+        Some(vec![
+            vec![0, 0, 0, 0, 0],
+            vec![0, 0, 0, 0, 1],
+            vec![0, 0, 0, 1, 0],
+            vec![0, 0, 1, 0, 0],
+            vec![0, 1, 0, 0, 0],
+            vec![1, 0, 0, 0, 0],
+            vec![0, 0, 0, 0, 0]
+        ])
+    );
+    assert_eq!(cycles.count(), 8);
+}
+
+#[test]
+fn test_map_cycle_to_word() {
+    use crate::perfect::is_perfect;
+
+    // Round trip, starting from any rotation of the cycles
+    for (length, order) in [(2, 3), (3, 3), (6, 3), (7, 3), (5, 4), (4, 2)] {
+        let lws = LyndonWord::new_smallest(length, 1)
+            .into_iter(true)
+            .filter(|w| is_perfect(w, order));
+        for lw in lws {
+            let cycle = map_word_to_cycle(lw.clone(), order);
+            assert_eq!(map_cycle_to_word(&cycle), Ok(lw.clone()));
+            for r in 1..length {
+                let mut rotated = cycle[r..length].to_vec();
+                rotated.extend_from_slice(&cycle[..r]);
+                assert_eq!(map_cycle_to_word(&rotated), Ok(lw.clone()));
+            }
+        }
+    }
+
+    // Single loops
+    assert_eq!(map_cycle_to_word(&vec![vec![1, 1, 1]]), Ok(vec![1]));
+
+    // Invalid inputs
+    assert!(map_cycle_to_word(&vec![]).is_err());
+    assert!(map_cycle_to_word(&vec![vec![0, 0, 1], vec![0, 1]]).is_err());
+    assert!(map_cycle_to_word(&vec![vec![0, 0, 1], vec![1, 1, 0]]).is_err());
+    assert!(
+        map_cycle_to_word(&vec![
+            vec![0, 0, 1],
+            vec![0, 1, 0],
+            vec![1, 0, 0],
+            vec![0, 0, 1],
+            vec![0, 1, 0],
+            vec![1, 0, 0]
+        ])
+        .is_err()
+    );
+}
+
+#[test]
+fn test_enum_cycles_fixed_content() {
+    // The cycles of a given length, split by content
+    for (length, order, sigma) in [(6, 3, 2), (8, 3, 2), (5, 2, 3)] {
+        let mut cycles = Vec::new();
+        for code in 0..usize::pow(length + 1, sigma as u32) {
+            let content = (0..sigma as u32)
+                .map(|i| (code / usize::pow(length + 1, i)) % (length + 1))
+                .collect::<Vec<_>>();
+            if content.iter().sum::<usize>() == length {
+                cycles.extend(enum_cycles_fixed_content(&content, order));
+            }
+        }
+        cycles.sort();
+        assert_eq!(cycles, enum_cycles_fixed_length(length, order, sigma));
+    }
+
+    // Balanced cycles of dBG(3, 2) of length 6
+    let cycles = enum_cycles_fixed_content(&[3, 3], 3);
+    assert_eq!(
+        cycles
+            .iter()
+            .map(|c| map_cycle_to_word(c).unwrap())
+            .collect::<Vec<_>>(),
+        [[0, 0, 0, 1, 1, 1], [0, 0, 1, 0, 1, 1], [0, 0, 1, 1, 0, 1]]
+    );
+}
+
+#[test]
+fn test_enum_cycles_up_to_reversal() {
+    use crate::lyndon::bracelet_representative;
+
+    // Every cycle is reached from exactly one representative, either directly
+    // or through reversal
+    for (length, order, sigma) in [(6, 3, 2), (7, 3, 2), (8, 3, 2), (6, 2, 3), (16, 4, 2)] {
+        let representatives = enum_cycles_up_to_reversal(length, order, sigma)
+            .iter()
+            .map(|c| map_cycle_to_word(c).unwrap())
+            .collect::<Vec<_>>();
+        for cycle in enum_cycles_fixed_length(length, order, sigma) {
+            let w = map_cycle_to_word(&cycle).unwrap();
+            let hits = representatives
+                .iter()
+                .filter(|r| **r == bracelet_representative(&w))
+                .count();
+            assert_eq!(hits, 1);
+        }
+    }
+
+    // 0010111 and 0011101 are the reversal of one another
+    let cycles = enum_cycles_up_to_reversal(7, 3, 2);
+    assert_eq!(cycles.len(), 2);
+    assert_eq!(
+        map_cycle_to_word(&cycles[0]).unwrap(),
+        [0, 0, 0, 1, 0, 1, 1]
+    );
+    assert_eq!(
+        map_cycle_to_word(&cycles[1]).unwrap(),
+        [0, 0, 1, 0, 1, 1, 1]
+    );
+}
+
+#[test]
+fn test_enum_cycles_through() {
+    // Compare with the filtering of all the cycles
+    for (order, sigma) in [(3, 2), (2, 3), (4, 2)] {
+        let max_length = usize::pow(sigma as usize, order as u32);
+        for length in 1..=max_length {
+            let cycles = enum_cycles_fixed_length(length, order, sigma);
+            for vertex in crate::words::all_words(order, sigma) {
+                let expected = cycles
+                    .iter()
+                    .filter(|c| c.contains(&vertex))
+                    .cloned()
+                    .collect::<Vec<_>>();
+                assert_eq!(enum_cycles_through(&vertex, length, order, sigma), expected);
+            }
+        }
+    }
+
+    // The only cycle of length 2 through 0.1.0
+    assert_eq!(
+        enum_cycles_through(&vec![0, 1, 0], 2, 3, 2),
+        [[[0, 1, 0], [1, 0, 1], [0, 1, 0]]]
+    );
+    assert!(enum_cycles_through(&vec![0, 1, 1], 2, 3, 2).is_empty());
+}
+
+#[test]
+fn test_enum_cycles_avoiding() {
+    // Removing a vertex removes the cycles going through it
+    for length in 1..=8 {
+        let forbidden = ForbiddenKmers::parse("0.1.1", 3, 2).unwrap();
+        let expected = enum_cycles_fixed_length(length, 3, 2)
+            .into_iter()
+            .filter(|c| !c.contains(&vec![0, 1, 1]))
+            .collect::<Vec<_>>();
+        assert_eq!(enum_cycles_avoiding(&forbidden, length, 3, 2), expected);
+    }
+
+    // Without homopolymers 000 and 111, nor the edge 0.1.0 -> 1.0.1, the
+    // cycles of length 6 of dBG(3, 2) are gone but one
+    let forbidden = ForbiddenKmers::parse("0.0.0\n1.1.1\n0.1.0.1", 3, 2).unwrap();
+    let cycles = enum_cycles_avoiding(&forbidden, 6, 3, 2);
+    assert_eq!(
+        cycles
+            .iter()
+            .map(|c| map_cycle_to_word(c).unwrap())
+            .collect::<Vec<_>>(),
+        [[0, 0, 1, 1, 0, 1]]
+    );
+}
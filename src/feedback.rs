@@ -0,0 +1,232 @@
+/**
+*
+* #### feedback.rs ####
+*
+* A (binary) feedback shift register of order k is given by a Boolean feedback
+* function f on k bits: it maps each state x_1...x_k to x_2...x_k f(x_1...x_k),
+* i.e. follows one of the two edges leaving x_1...x_k in dBG(k, 2). Its state
+* graph is thus a subgraph of the de Bruijn graph, where each vertex has a
+* single successor, and whose cycles are disjoint simple cycles of dBG(k, 2),
+* encoded by perfect Lyndon words.
+*
+* The state graph is a permutation of the vertices (i.e. it is the disjoint
+* union of its cycles) iff the register is nonsingular, that is iff f can be
+* written x_1 + g(x_2...x_k). Otherwise, some states are transient and only lead
+* to the cycles. E.g. f = x_1 is the pure cycling register, whose cycles are the
+* necklaces (see joining.rs), and f = x_1 + x_2 is a linear register whose
+* cycles are 0.0.0 and a de Bruijn cycle of dBG(3, 2) with 0.0.0 removed.
+*
+* Feedback functions are given either by their truth table, indexed by the
+* states in lexicographic order, or by their algebraic normal form (ANF), i.e.
+* a sum (XOR) of monomials.
+*
+*
+* Source(s).
+* [1] "Shift register sequences", Solomon W. GOLOMB (1967)
+*
+**/
+use crate::r#enum::{map_cycle_to_word, map_word_to_cycle};
+use crate::words::{Cycle, Word};
+
+// The number 2^k of states, or an error if it does not fit in a usize
+fn nb_states(order: usize) -> Result<usize, String> {
+    match order < usize::BITS as usize {
+        true => Ok(1 << order),
+        false => Err(format!("A register of order {} has too many states", order)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedbackFunction {
+    order: usize,
+    truth_table: Vec<bool>,
+}
+
+impl FeedbackFunction {
+    // The feedback function whose value on the state of code i (in base 2, the
+    // first bit being the most significant) is truth_table[i]
+    pub fn from_truth_table(order: usize, truth_table: Vec<bool>) -> Result<Self, String> {
+        let nb_states = nb_states(order)?;
+        if truth_table.len() != nb_states {
+            return Err(format!(
+                "A truth table on {} bits should have {} entries, not {}",
+                order,
+                nb_states,
+                truth_table.len()
+            ));
+        }
+        Ok(FeedbackFunction { order, truth_table })
+    }
+
+    // The feedback function given by its algebraic normal form, as a list of
+    // monomials, each of them being the list of its variables (from 1 to k),
+    // e.g. [[1], [2, 3], []] for x_1 + x_2 x_3 + 1
+    pub fn from_anf(order: usize, monomials: &[Vec<usize>]) -> Result<Self, String> {
+        if let Some(i) = monomials.iter().flatten().find(|&&i| i == 0 || i > order) {
+            return Err(format!("x_{} is not a variable on {} bits", i, order));
+        }
+        let truth_table = (0..nb_states(order)?)
+            .map(|code: usize| {
+                let bit = |i: usize| (code >> (order - i)) & 1 == 1;
+                monomials
+                    .iter()
+                    .filter(|monomial| monomial.iter().all(|&i| bit(i)))
+                    .count()
+                    % 2
+                    == 1
+            })
+            .collect();
+        Ok(FeedbackFunction { order, truth_table })
+    }
+
+    // Parse a truth table, written as a sequence of 2^k bits (e.g. 01010101 for
+    // x_3 on 3 bits). Whitespace is ignored, as well as lines starting with #.
+    pub fn parse(text: &str, order: usize) -> Result<Self, String> {
+        let mut truth_table = Vec::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.starts_with('#') {
+                continue;
+            }
+            for c in line.chars().filter(|c| !c.is_whitespace()) {
+                match c {
+                    '0' => truth_table.push(false),
+                    '1' => truth_table.push(true),
+                    _ => return Err(format!("Line {}: {:?} is not a bit", i + 1, c)),
+                }
+            }
+        }
+        FeedbackFunction::from_truth_table(order, truth_table)
+    }
+
+    pub fn order(&self) -> usize {
+        self.order
+    }
+
+    // The value of the feedback function on a state
+    pub fn eval(&self, state: &Word) -> bool {
+        let code = state.iter().fold(0, |code, &a| 2 * code + a as usize);
+        self.truth_table[code]
+    }
+
+    // The state following a given one
+    pub fn successor(&self, state: &Word) -> Word {
+        let mut next = state[1..].to_vec();
+        next.push(self.eval(state) as u8);
+        next
+    }
+
+    // Whether the state graph is a permutation, i.e. whether the two states
+    // x_1 x_2...x_k that share x_2...x_k always have distinct images under f
+    pub fn is_nonsingular(&self) -> bool {
+        let half = self.truth_table.len() / 2;
+        (0..half).all(|i| self.truth_table[i] != self.truth_table[half + i])
+    }
+
+    // The perfect Lyndon words encoding the cycles of the state graph, in
+    // lexicographic order. Each state is followed until a known state is met,
+    // which closes a cycle if it was met during the same walk.
+    pub fn perfect_lyndon_words(&self) -> Vec<Word> {
+        let n = self.truth_table.len();
+        let mut walk_of = vec![None; n];
+        let mut words = Vec::new();
+        for start in 0..n {
+            let mut states = Vec::new();
+            let mut code = start;
+            while walk_of[code].is_none() {
+                walk_of[code] = Some(start);
+                states.push(code);
+                code = (2 * code) % n + self.truth_table[code] as usize;
+            }
+            if walk_of[code] == Some(start) {
+                let first = states.iter().position(|&c| c == code).unwrap();
+                let cycle = states[first..]
+                    .iter()
+                    .map(|&c| (0..self.order).rev().map(|i| (c >> i) as u8 & 1).collect())
+                    .collect::<Cycle>();
+                words.push(map_cycle_to_word(&cycle).unwrap());
+            }
+        }
+        words.sort();
+        words
+    }
+
+    // The cycles of the state graph, as output by the enumeration (i.e. closed,
+    // and starting with their perfect Lyndon word), in the same order
+    pub fn cycles(&self) -> Vec<Cycle> {
+        self.perfect_lyndon_words()
+            .into_iter()
+            .map(|plw| map_word_to_cycle(plw, self.order))
+            .collect()
+    }
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_feedback_function() {
+    let f = FeedbackFunction::parse("# x_1 + x_3\n0101\n 1010\n", 3).unwrap();
+    assert_eq!(
+        f,
+        FeedbackFunction::from_anf(3, &[vec![1], vec![3]]).unwrap()
+    );
+    assert_eq!(f.successor(&vec![1, 1, 0]), [1, 0, 1]);
+    assert!(f.is_nonsingular());
+    assert!(FeedbackFunction::parse("0101", 3).is_err());
+    assert!(FeedbackFunction::parse("01x1", 2).is_err());
+    assert!(FeedbackFunction::from_anf(3, &[vec![4]]).is_err());
+    assert!(FeedbackFunction::from_anf(64, &[vec![1]]).is_err());
+    assert!(FeedbackFunction::from_truth_table(usize::BITS as usize, vec![]).is_err());
+
+    // x_1 x_2 + 1 is singular
+    let f = FeedbackFunction::from_anf(2, &[vec![1, 2], vec![]]).unwrap();
+    assert!(!f.is_nonsingular());
+}
+
+#[test]
+fn test_feedback_cycles() {
+    use crate::dbs::{Construction, de_bruijn_sequence};
+    use crate::joining::pcr_cycles;
+
+    // The pure cycling register
+    for order in 1..=8 {
+        let f = FeedbackFunction::from_anf(order, &[vec![1]]).unwrap();
+        assert_eq!(f.cycles(), pcr_cycles(order, 2));
+    }
+
+    // The linear register of the primitive polynomial X^3 + X + 1
+    let f = FeedbackFunction::from_anf(3, &[vec![1], vec![2]]).unwrap();
+    assert_eq!(
+        f.perfect_lyndon_words(),
+        [vec![0], vec![0, 0, 1, 0, 1, 1, 1]]
+    );
+
+    // The register that generates a de Bruijn sequence
+    for order in 1..=8 {
+        let dbs = de_bruijn_sequence(order, 2, Construction::Granddaddy).unwrap();
+        let n = dbs.len();
+        let mut truth_table = vec![false; n];
+        for i in 0..n {
+            let code = (0..order).fold(0, |code, j| 2 * code + dbs[(i + j) % n] as usize);
+            truth_table[code] = dbs[(i + order) % n] == 1;
+        }
+        let f = FeedbackFunction::from_truth_table(order, truth_table).unwrap();
+        assert!(f.is_nonsingular());
+        assert_eq!(f.perfect_lyndon_words(), [dbs]);
+    }
+
+    // The singular register x_1 x_2 + 1 has the only cycle 0.1 -> 1.1 -> 1.0,
+    // the state 0.0 being transient
+    let f = FeedbackFunction::from_anf(2, &[vec![1, 2], vec![]]).unwrap();
+    assert_eq!(f.cycles(), [[[0, 1], [1, 1], [1, 0], [0, 1]]]);
+}
@@ -0,0 +1,224 @@
+/**
+*
+* #### johnson.rs ####
+*
+* An independent oracle for the enumeration of simple cycles, that does not
+* rely on Lyndon words at all: the de Bruijn graph is built explicitly, as an
+* adjacency structure, and its elementary circuits are enumerated with the
+* algorithm of Johnson [1]. This is exponentially slower than the enumeration of
+* enum.rs (all cycles of all lengths are listed), but allows to differentially
+* test it, as well as the bijection it relies on.
+*
+* Vertices are identified with their index, i.e. their code in base sigma, so
+* that the order on indices is the lexicographic order on k-mers. Johnson's
+* algorithm lists each circuit from its smallest vertex, that is, from the
+* same vertex as map_word_to_cycle (the least rotation of a perfect word
+* starting with its smallest k-mer).
+*
+*
+* Source(s).
+* [1] "Finding all the elementary circuits of a directed graph", Donald B.
+* JOHNSON (1975)
+*
+**/
+use crate::words::{Cycle, Word};
+
+pub struct DeBruijnGraph {
+    order: usize,
+    sigma: u8,
+    successors: Vec<Vec<usize>>,
+}
+
+impl DeBruijnGraph {
+    pub fn new(order: usize, sigma: u8) -> Self {
+        let nb_vertices = usize::pow(sigma as usize, order as u32);
+        let successors = (0..nb_vertices)
+            .map(|u| {
+                let shifted = (u * sigma as usize) % nb_vertices;
+                (0..sigma as usize).map(|a| shifted + a).collect()
+            })
+            .collect();
+        DeBruijnGraph {
+            order,
+            sigma,
+            successors,
+        }
+    }
+
+    pub fn nb_vertices(&self) -> usize {
+        self.successors.len()
+    }
+
+    // The k-mer of a vertex, given its index
+    pub fn vertex(&self, index: usize) -> Word {
+        let mut word = vec![0; self.order];
+        let mut code = index;
+        for letter in word.iter_mut().rev() {
+            *letter = (code % self.sigma as usize) as u8;
+            code /= self.sigma as usize;
+        }
+        word
+    }
+
+    // The vertices from which start vertex s can be reached, and that are
+    // reachable from s, using only vertices no smaller than s. This is the
+    // strongly connected component of s in the subgraph induced by these
+    // vertices, out of which no circuit through s can go.
+    fn component(&self, s: usize) -> Vec<bool> {
+        let n = self.nb_vertices();
+        let reach = |successors: &dyn Fn(usize) -> Vec<usize>| {
+            let mut seen = vec![false; n];
+            let mut stack = vec![s];
+            seen[s] = true;
+            while let Some(u) = stack.pop() {
+                for v in successors(u) {
+                    if v >= s && !seen[v] {
+                        seen[v] = true;
+                        stack.push(v);
+                    }
+                }
+            }
+            seen
+        };
+        let forward = reach(&|u| self.successors[u].clone());
+        let backward = reach(&|u| {
+            // The predecessors of u are obtained by prepending any letter to
+            // its k-1 first letters
+            let sigma = self.sigma as usize;
+            (0..sigma).map(|a| a * (n / sigma) + u / sigma).collect()
+        });
+        (0..n).map(|u| forward[u] && backward[u]).collect()
+    }
+}
+
+// The state of the search for the circuits starting at some vertex s, as
+// described in [1]
+struct Search<'a> {
+    graph: &'a DeBruijnGraph,
+    s: usize,
+    component: Vec<bool>,
+    blocked: Vec<bool>,
+    b: Vec<Vec<usize>>,
+    stack: Vec<usize>,
+    circuits: Vec<Vec<usize>>,
+}
+
+impl Search<'_> {
+    fn unblock(&mut self, u: usize) {
+        self.blocked[u] = false;
+        while let Some(w) = self.b[u].pop() {
+            if self.blocked[w] {
+                self.unblock(w);
+            }
+        }
+    }
+
+    fn circuit(&mut self, v: usize) -> bool {
+        let mut found = false;
+        self.stack.push(v);
+        self.blocked[v] = true;
+        for &w in self.graph.successors[v].iter() {
+            if !self.component[w] {
+                continue;
+            }
+            if w == self.s {
+                self.circuits.push(self.stack.clone());
+                found = true;
+            } else if !self.blocked[w] && self.circuit(w) {
+                found = true;
+            }
+        }
+        if found {
+            self.unblock(v);
+        } else {
+            for &w in self.graph.successors[v].iter() {
+                if self.component[w] && !self.b[w].contains(&v) {
+                    self.b[w].push(v);
+                }
+            }
+        }
+        self.stack.pop();
+        found
+    }
+}
+
+// All the simple cycles of the de Bruijn graph, as sequences of vertices that
+// start at their smallest vertex, and are closed (as the cycles of enum.rs).
+// They are sorted by length first, and then lexicographically.
+pub fn johnson_cycles(order: usize, sigma: u8) -> Vec<Cycle> {
+    let graph = DeBruijnGraph::new(order, sigma);
+    let n = graph.nb_vertices();
+
+    let mut circuits = Vec::new();
+    for s in 0..n {
+        let mut search = Search {
+            graph: &graph,
+            s,
+            component: graph.component(s),
+            blocked: vec![false; n],
+            b: vec![Vec::new(); n],
+            stack: Vec::new(),
+            circuits: Vec::new(),
+        };
+        search.circuit(s);
+        circuits.extend(search.circuits);
+    }
+
+    let mut cycles = circuits
+        .into_iter()
+        .map(|circuit| {
+            let mut cycle = circuit.iter().map(|&u| graph.vertex(u)).collect::<Cycle>();
+            cycle.push(cycle[0].clone());
+            cycle
+        })
+        .collect::<Vec<_>>();
+    cycles.sort_by(|x, y| (x.len(), x).cmp(&(y.len(), y)));
+    cycles
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_de_bruijn_graph() {
+    let graph = DeBruijnGraph::new(3, 2);
+    assert_eq!(graph.nb_vertices(), 8);
+    assert_eq!(graph.vertex(6), [1, 1, 0]);
+    assert_eq!(graph.successors[6], [4, 5]);
+    assert_eq!(graph.component(0).iter().filter(|&&b| b).count(), 8);
+    // Above 1.1.0, only 1.1.1 remains, that cannot reach 1.1.0 back
+    assert_eq!(graph.component(6).iter().filter(|&&b| b).count(), 1);
+}
+
+#[test]
+fn test_johnson_cycles() {
+    use crate::count::count_cycles_with_formula;
+    use crate::r#enum::enum_cycles_bounded_length;
+    use num::BigUint;
+
+    // Differential testing of the enumeration through perfect Lyndon words
+    for (order, sigma) in [(1, 2), (1, 3), (1, 4), (2, 2), (3, 2), (4, 2), (2, 3)] {
+        let max_length = usize::pow(sigma as usize, order as u32);
+        let cycles = johnson_cycles(order, sigma);
+
+        let mut expected = enum_cycles_bounded_length(max_length, order, sigma);
+        expected.sort_by(|x, y| (x.len(), x).cmp(&(y.len(), y)));
+        assert_eq!(cycles, expected);
+
+        // ... and of the counting formulas
+        for length in 1..=max_length {
+            let nb_cycles = cycles.iter().filter(|c| c.len() == length + 1).count();
+            let count = count_cycles_with_formula(length, order, sigma, false);
+            assert_eq!(count.to_option().unwrap(), BigUint::from(nb_cycles));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+/**
+*
+* #### joining.rs ####
+*
+* De Bruijn sequences can be built by joining small cycles: starting from a
+* partition of the vertices of dBG(k, sigma) into disjoint simple cycles, two
+* cycles going through conjugate vertices a.x and b.x (that only differ by
+* their first letter, hence share their successors) are merged into one by
+* swapping the successors of these vertices. Merging the cycles along a
+* spanning tree of the graph of conjugate pairs (the cycle-joining tree) yields
+* a single cycle through all the vertices, i.e. a de Bruijn sequence [1, 2].
+*
+* The classic starting partition is given by the pure cycling register (PCR),
+* that maps a.x to x.a: its cycles are the necklaces of length k, i.e. the
+* cycles encoded by the Lyndon words whose length divides k.
+*
+* Seeing the successor function as a permutation of the vertices, a swap is the
+* composition with a transposition, that merges two cycles whenever they are
+* distinct. The tree is thus built with a union-find structure on the cycles,
+* and its swaps can be applied in any order.
+*
+*
+* Source(s).
+* [1] "A survey of full length nonlinear shift register cycle algorithms",
+* Harold FREDRICKSEN (1982)
+* [2] "Generalizing the classic greedy and necklace constructions of de Bruijn
+* sequences and universal cycles", Joe SAWADA, Aaron WILLIAMS and Dennis WONG
+* (2016)
+*
+**/
+use crate::r#enum::{map_cycle_to_word, map_word_to_cycle};
+use crate::lyndon::LyndonWord;
+use crate::perfect::PerfectnessChecker;
+use crate::words::{Cycle, Word};
+use std::collections::HashMap;
+
+// Two conjugate vertices a.x and b.x, lying on the cycles of the given indices
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConjugatePair {
+    pub cycles: (usize, usize),
+    pub vertices: (Word, Word),
+}
+
+// The cycles of the pure cycling register of order k, one per necklace of
+// length k, in the lexicographic order of their Lyndon words
+pub fn pcr_cycles(order: usize, sigma: u8) -> Vec<Cycle> {
+    let mut lw = LyndonWord::new_smallest(order, sigma - 1);
+    lw.iter(false)
+        .filter(|w| order.is_multiple_of(w.len()))
+        .map(|w| map_word_to_cycle(w, order))
+        .collect()
+}
+
+// The cycle on which each vertex lies, or an error if the cycles are not simple
+// cycles of dBG(k, sigma) partitioning its vertices
+fn cycle_indices(
+    cycles: &[Cycle],
+    order: usize,
+    sigma: u8,
+) -> Result<HashMap<Word, usize>, String> {
+    let mut index = HashMap::new();
+    for (i, cycle) in cycles.iter().enumerate() {
+        map_cycle_to_word(cycle)?;
+        let mut vertices = &cycle[..];
+        if vertices.len() > 1 && vertices.first() == vertices.last() {
+            vertices = &vertices[..vertices.len() - 1];
+        }
+        for vertex in vertices {
+            if vertex.len() != order || vertex.iter().any(|&a| a >= sigma) {
+                return Err(format!(
+                    "{:?} is not a vertex of dBG({}, {})",
+                    vertex, order, sigma
+                ));
+            }
+            if index.insert(vertex.clone(), i).is_some() {
+                return Err(format!("The vertex {:?} lies on several cycles", vertex));
+            }
+        }
+    }
+    if index.len() != usize::pow(sigma as usize, order as u32) {
+        return Err("The cycles do not cover all the vertices".to_string());
+    }
+    Ok(index)
+}
+
+// All the pairs of conjugate vertices a.x and b.x (with a < b) lying on distinct
+// cycles of the partition, in lexicographic order
+pub fn conjugate_pairs(
+    cycles: &[Cycle],
+    order: usize,
+    sigma: u8,
+) -> Result<Vec<ConjugatePair>, String> {
+    let index = cycle_indices(cycles, order, sigma)?;
+    let mut vertices = index.keys().cloned().collect::<Vec<_>>();
+    vertices.sort();
+
+    let mut pairs = Vec::new();
+    for u in vertices.iter() {
+        for b in u[0] + 1..sigma {
+            let mut v = u.clone();
+            v[0] = b;
+            if index[u] != index[&v] {
+                pairs.push(ConjugatePair {
+                    cycles: (index[u], index[&v]),
+                    vertices: (u.clone(), v),
+                });
+            }
+        }
+    }
+    Ok(pairs)
+}
+
+// A cycle-joining tree, i.e. conjugate pairs connecting all the cycles without
+// redundancy, picked greedily in lexicographic order. An error is returned if
+// the cycles cannot all be joined.
+pub fn joining_tree(
+    cycles: &[Cycle],
+    order: usize,
+    sigma: u8,
+) -> Result<Vec<ConjugatePair>, String> {
+    fn find(parent: &mut Vec<usize>, i: usize) -> usize {
+        if parent[i] != i {
+            parent[i] = find(parent, parent[i]);
+        }
+        parent[i]
+    }
+
+    let mut parent = (0..cycles.len()).collect::<Vec<_>>();
+    let mut tree = Vec::new();
+    for pair in conjugate_pairs(cycles, order, sigma)? {
+        let (i, j) = (
+            find(&mut parent, pair.cycles.0),
+            find(&mut parent, pair.cycles.1),
+        );
+        if i != j {
+            parent[i] = j;
+            tree.push(pair);
+        }
+    }
+    if tree.len() + 1 != cycles.len() {
+        return Err("The cycles cannot be joined through conjugate pairs".to_string());
+    }
+    Ok(tree)
+}
+
+// The de Bruijn sequence obtained by joining the cycles along a cycle-joining
+// tree, starting with 0^k
+pub fn join_cycles(cycles: &[Cycle], order: usize, sigma: u8) -> Result<Word, String> {
+    let tree = joining_tree(cycles, order, sigma)?;
+
+    let mut successor = HashMap::new();
+    for cycle in cycles {
+        for edge in cycle.windows(2) {
+            successor.insert(edge[0].clone(), edge[1].clone());
+        }
+        // Cycles that are not closed
+        if cycle.len() == 1 || cycle.first() != cycle.last() {
+            successor.insert(cycle[cycle.len() - 1].clone(), cycle[0].clone());
+        }
+    }
+    for pair in tree {
+        let (u, v) = pair.vertices;
+        let successor_of_u = successor[&u].clone();
+        let successor_of_v = successor.insert(v, successor_of_u).unwrap();
+        successor.insert(u, successor_of_v);
+    }
+
+    let mut sequence = Vec::with_capacity(successor.len());
+    let mut vertex = vec![0; order];
+    for _ in 0..successor.len() {
+        sequence.push(vertex[0]);
+        vertex = successor[&vertex].clone();
+    }
+    if !PerfectnessChecker::new(order, sigma).is_perfect(&sequence) {
+        return Err("The joined cycles do not form a de Bruijn sequence".to_string());
+    }
+    Ok(sequence)
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_pcr_cycles() {
+    // The necklaces of length 4: 0000, 0001, 0011, 0101, 0111 and 1111
+    let cycles = pcr_cycles(4, 2);
+    assert_eq!(cycles.len(), 6);
+    assert_eq!(cycles[0], [[0, 0, 0, 0], [0, 0, 0, 0]]);
+    assert_eq!(cycles[3], [[0, 1, 0, 1], [1, 0, 1, 0], [0, 1, 0, 1]]);
+    assert!(cycle_indices(&cycles, 4, 2).is_ok());
+
+    // The necklaces of length 3 over 3 letters
+    assert_eq!(pcr_cycles(3, 3).len(), 11);
+}
+
+#[test]
+fn test_join_cycles() {
+    for (max_order, sigma) in [(10, 2), (5, 3), (4, 4), (3, 5)] {
+        for order in 1..=max_order {
+            let cycles = pcr_cycles(order, sigma);
+            let tree = joining_tree(&cycles, order, sigma).unwrap();
+            assert_eq!(tree.len() + 1, cycles.len());
+            let sequence = join_cycles(&cycles, order, sigma).unwrap();
+            assert_eq!(sequence.len(), usize::pow(sigma as usize, order as u32));
+        }
+    }
+
+    // dBG(3, 2) partitioned into its two loops and a cycle of length 6, that
+    // is joined to them through 0.0.0 ~ 1.0.0 and 0.1.1 ~ 1.1.1
+    let cycles = vec![
+        vec![vec![0, 0, 0]],
+        vec![vec![1, 1, 1]],
+        map_word_to_cycle(vec![0, 0, 1, 0, 1, 1], 3),
+    ];
+    let tree = joining_tree(&cycles, 3, 2).unwrap();
+    assert_eq!(tree[0].vertices, (vec![0, 0, 0], vec![1, 0, 0]));
+    assert_eq!(tree[1].vertices, (vec![0, 1, 1], vec![1, 1, 1]));
+    assert_eq!(
+        join_cycles(&cycles, 3, 2).unwrap(),
+        [0, 0, 0, 1, 0, 1, 1, 1]
+    );
+
+    // Cycles that do not partition the vertices
+    let mut overlapping = pcr_cycles(3, 2);
+    overlapping.push(vec![vec![0, 0, 0]]);
+    assert!(join_cycles(&overlapping, 3, 2).is_err());
+    assert!(join_cycles(&pcr_cycles(3, 2)[1..], 3, 2).is_err());
+}
@@ -0,0 +1,330 @@
+/**
+*
+* #### lfsr.rs ####
+*
+* When sigma is a prime p, letters are the elements of the field GF(p), and a
+* monic polynomial c(X) = X^k + c_(k-1) X^(k-1) + ... + c_0 defines a linear
+* feedback shift register of order k, that outputs the sequences satisfying
+* s_(n+k) = - (c_(k-1) s_(n+k-1) + ... + c_0 s_n). When c is primitive, i.e. X
+* generates the multiplicative group of GF(p)[X]/(c), any non-zero initial
+* state yields an m-sequence, of period p^k - 1: all the k-mers but 0^k appear
+* exactly once in it, so that it is a simple cycle of dBG(k, p) through all
+* the vertices but 0^k. There are phi(p^k - 1) / k primitive polynomials of
+* degree k, that yield as many distinct such cycles [1].
+*
+* A polynomial is primitive iff X has order p^k - 1 modulo c, which is tested
+* by raising X to (p^k - 1) / q for each prime factor q of p^k - 1. Conversely,
+* a cycle of length p^k - 1 is an m-sequence iff its linear complexity (the
+* order of the smallest register generating it, computed with the algorithm of
+* Berlekamp and Massey [2]) is k.
+*
+* Polynomials are represented by their coefficients, from c_0 to the leading 1.
+*
+*
+* Source(s).
+* [1] "Shift register sequences", Solomon W. GOLOMB (1967)
+* [2] "Shift-register synthesis and BCH decoding", James L. MASSEY (1969)
+*
+**/
+use crate::r#enum::{map_cycle_to_word, map_word_to_cycle};
+use crate::lyndon::conjugacy_class_representative;
+use crate::words::{Cycle, Letter, Word};
+use red_primality::{factor, is_u64_prime};
+
+fn check_prime(p: u8) -> Result<(), String> {
+    match is_u64_prime(p as u64) {
+        true => Ok(()),
+        false => Err(format!("The alphabet size {} is not a prime", p)),
+    }
+}
+
+// The inverse of a non-zero element of GF(p), following Fermat's little theorem
+fn inverse(a: u64, p: u64) -> u64 {
+    (0..p - 2).fold(1, |x, _| x * a % p)
+}
+
+// The product of two polynomials of degree less than k, modulo the monic
+// polynomial c of degree k
+fn mul_mod(a: &[u64], b: &[u64], c: &[Letter], p: u64) -> Vec<u64> {
+    let k = c.len() - 1;
+    let mut product = vec![0; 2 * k];
+    for (i, &x) in a.iter().enumerate() {
+        for (j, &y) in b.iter().enumerate() {
+            product[i + j] = (product[i + j] + x * y) % p;
+        }
+    }
+    // X^k = - (c_(k-1) X^(k-1) + ... + c_0)
+    for d in (k..2 * k).rev() {
+        let x = product[d];
+        for i in 0..k {
+            product[d - k + i] = (product[d - k + i] + (p - x) * c[i] as u64) % p;
+        }
+        product[d] = 0;
+    }
+    product.truncate(k);
+    product
+}
+
+// X^e modulo the monic polynomial c, by square-and-multiply
+fn pow_x_mod(mut e: u64, c: &[Letter], p: u64) -> Vec<u64> {
+    let k = c.len() - 1;
+    let mut result = vec![0; k];
+    result[0] = 1;
+    // X itself, that is already reduced unless k = 1 (then X = - c_0)
+    let mut base = vec![0; k];
+    match k {
+        1 => base[0] = (p - c[0] as u64) % p,
+        _ => base[1] = 1,
+    }
+    while e > 0 {
+        if e & 1 == 1 {
+            result = mul_mod(&result, &base, c, p);
+        }
+        base = mul_mod(&base, &base, c, p);
+        e >>= 1;
+    }
+    result
+}
+
+// The order p^k - 1 of the multiplicative group of GF(p^k), if it fits in a u64
+fn group_order(degree: usize, p: u8) -> Option<u64> {
+    let k = u32::try_from(degree).ok()?;
+    u64::checked_pow(p as u64, k).map(|q| q - 1)
+}
+
+// Whether X has order n = p^k - 1 modulo the monic polynomial c of degree k > 0,
+// given the prime factors of n
+fn has_group_order(polynomial: &[Letter], p: u8, n: u64, factors: &[u64]) -> bool {
+    let one = |x: Vec<u64>| x[0] == 1 && x[1..].iter().all(|&a| a == 0);
+    polynomial[0] != 0
+        && one(pow_x_mod(n, polynomial, p as u64))
+        && factors
+            .iter()
+            .all(|&q| !one(pow_x_mod(n / q, polynomial, p as u64)))
+}
+
+// Whether a monic polynomial (given with its leading 1) is primitive over GF(p).
+// This is always false when p is not a prime, as GF(p) is then not a field, and
+// when p^k - 1 does not fit in a u64.
+pub fn is_primitive(polynomial: &[Letter], p: u8) -> bool {
+    if polynomial.len() < 2 || check_prime(p).is_err() {
+        return false;
+    }
+    let k = polynomial.len() - 1;
+    if polynomial[k] != 1 {
+        return false;
+    }
+    let Some(n) = group_order(k, p) else {
+        return false;
+    };
+    let factors = factor(n).iter().map(|(q, _)| q.get()).collect::<Vec<_>>();
+    has_group_order(polynomial, p, n, &factors)
+}
+
+// All the primitive polynomials of degree k over GF(p), in lexicographic order
+// of their coefficients (from c_0). The prime factors of p^k - 1 are computed
+// once for all the candidates.
+pub fn primitive_polynomials(degree: usize, p: u8) -> Result<Vec<Vec<Letter>>, String> {
+    check_prime(p)?;
+    if degree == 0 {
+        return Ok(Vec::new());
+    }
+    let too_many = || format!("There are too many polynomials of degree {}", degree);
+    let nb_candidates = u32::try_from(degree)
+        .ok()
+        .and_then(|k| usize::checked_pow(p as usize, k))
+        .ok_or_else(too_many)?;
+    let n = group_order(degree, p).ok_or_else(too_many)?;
+    let factors = factor(n).iter().map(|(q, _)| q.get()).collect::<Vec<_>>();
+    let polynomials = (0..nb_candidates)
+        .map(|code| {
+            let mut polynomial = (0..degree)
+                .rev()
+                .map(|i| (code / usize::pow(p as usize, i as u32) % p as usize) as Letter)
+                .collect::<Vec<_>>();
+            polynomial.push(1);
+            polynomial
+        })
+        .filter(|polynomial| has_group_order(polynomial, p, n, &factors))
+        .collect();
+    Ok(polynomials)
+}
+
+// The sequence output by the register of a polynomial, from the state
+// 0^(k-1) 1, over one period of an m-sequence (i.e. p^k - 1 letters). An error
+// is returned if p is not a prime, or if the polynomial is not monic of positive
+// degree.
+pub fn m_sequence(polynomial: &[Letter], p: u8) -> Result<Word, String> {
+    check_prime(p)?;
+    if polynomial.len() < 2 || polynomial.last() != Some(&1) {
+        return Err(format!(
+            "{:?} is not a monic polynomial of positive degree",
+            polynomial
+        ));
+    }
+    let k = polynomial.len() - 1;
+    let Some(n) = u32::try_from(k)
+        .ok()
+        .and_then(|k| usize::checked_pow(p as usize, k))
+        .map(|q| q - 1)
+    else {
+        return Err(format!("An m-sequence of order {} is too long", k));
+    };
+    let mut sequence = vec![0; k - 1];
+    sequence.push(1);
+    while sequence.len() < n {
+        let i = sequence.len() - k;
+        let sum = (0..k)
+            .map(|j| polynomial[j] as usize * sequence[i + j] as usize)
+            .sum::<usize>();
+        sequence.push(((p as usize - sum % p as usize) % p as usize) as Letter);
+    }
+    sequence.truncate(n);
+    Ok(sequence)
+}
+
+// The m-sequences of dBG(k, p), as cycles output by the enumeration (closed,
+// and starting with their perfect Lyndon word), sorted as such
+pub fn m_sequence_cycles(order: usize, p: u8) -> Result<Vec<Cycle>, String> {
+    let mut words = Vec::new();
+    for polynomial in primitive_polynomials(order, p)? {
+        words.push(conjugacy_class_representative(&m_sequence(&polynomial, p)?));
+    }
+    words.sort();
+    Ok(words
+        .into_iter()
+        .map(|plw| map_word_to_cycle(plw, order))
+        .collect())
+}
+
+// The length of the smallest linear feedback shift register over GF(p) that
+// generates a (linear) sequence, following the algorithm of Berlekamp-Massey
+pub fn linear_complexity(sequence: &[Letter], p: u8) -> usize {
+    let p = p as u64;
+    let mut c = vec![1];
+    let mut b = vec![1];
+    let (mut l, mut m, mut last_discrepancy) = (0, 1, 1);
+    for n in 0..sequence.len() {
+        let d = (0..=l).fold(0, |d, i| {
+            (d + c.get(i).unwrap_or(&0) * sequence[n - i] as u64) % p
+        });
+        if d == 0 {
+            m += 1;
+            continue;
+        }
+        // c <- c - d / last_discrepancy X^m b
+        let coef = d * inverse(last_discrepancy, p) % p;
+        let previous = c.clone();
+        c.resize(c.len().max(b.len() + m), 0);
+        for (i, &x) in b.iter().enumerate() {
+            c[i + m] = (c[i + m] + (p - coef) * x) % p;
+        }
+        if 2 * l <= n {
+            l = n + 1 - l;
+            b = previous;
+            last_discrepancy = d;
+            m = 1;
+        } else {
+            m += 1;
+        }
+    }
+    l
+}
+
+// Whether a simple cycle of dBG(k, p) is an m-sequence. The linear complexity
+// of a periodic sequence is obtained by running Berlekamp-Massey over two of
+// its periods.
+pub fn is_m_sequence_cycle(cycle: &Cycle, p: u8) -> bool {
+    let Ok(word) = map_cycle_to_word(cycle) else {
+        return false;
+    };
+    let order = cycle[0].len();
+    let length = u32::try_from(order)
+        .ok()
+        .and_then(|k| usize::checked_pow(p as usize, k));
+    if check_prime(p).is_err() || length != Some(word.len() + 1) {
+        return false;
+    }
+    linear_complexity(&word.repeat(2), p) == order
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_primitive_polynomials() {
+    use reikna::totient::totient as phi;
+
+    // X^3 + X^2 + 1 and X^3 + X + 1, while X^3 + 1 = (X + 1)(X^2 + X + 1)
+    assert_eq!(
+        primitive_polynomials(3, 2).unwrap(),
+        [[1, 0, 1, 1], [1, 1, 0, 1]]
+    );
+    assert!(!is_primitive(&[1, 0, 0, 1], 2));
+    // X^4 + X^3 + X^2 + X + 1 is irreducible, but X has order 5
+    assert!(!is_primitive(&[1, 1, 1, 1, 1], 2));
+    // 2 and 3 generate GF(5)*
+    assert_eq!(primitive_polynomials(1, 5).unwrap(), [[2, 1], [3, 1]]);
+    assert!(primitive_polynomials(2, 4).is_err());
+    // X + 2 generates (Z/4Z)*, which is not the multiplicative group of a field
+    assert!(!is_primitive(&[2, 1], 4));
+    assert!(!is_primitive(&[1], 2));
+    // Beyond u64 and usize, p^k - 1 overflows
+    assert!(!is_primitive(&[vec![1], vec![0; 63], vec![1]].concat(), 2));
+    assert!(primitive_polynomials(64, 2).is_err());
+
+    for (max_degree, p) in [(12, 2), (6, 3), (4, 5), (3, 7)] {
+        for degree in 1..=max_degree {
+            let n = u64::pow(p as u64, degree as u32) - 1;
+            let polynomials = primitive_polynomials(degree, p).unwrap();
+            assert_eq!(polynomials.len() as u64, phi(n) / degree as u64);
+        }
+    }
+}
+
+#[test]
+fn test_m_sequences() {
+    use crate::r#enum::enum_cycles_fixed_length;
+    use crate::perfect::PerfectnessChecker;
+
+    assert_eq!(m_sequence(&[1, 1, 0, 1], 2).unwrap(), [0, 0, 1, 0, 1, 1, 1]);
+    assert!(m_sequence(&[1], 2).is_err());
+    assert!(m_sequence(&[1, 1, 1], 4).is_err());
+    assert!(m_sequence(&[1; 65], 2).is_err());
+    assert!(!is_m_sequence_cycle(&vec![vec![0; 70]; 2], 2));
+    assert_eq!(linear_complexity(&[0, 0, 1, 0, 1, 1, 1].repeat(2), 2), 3);
+    // The generating polynomial X^3 + X^4 + X^5 of this sequence is coprime to
+    // X^7 - 1, so that its minimal polynomial is X^7 - 1 itself
+    assert_eq!(linear_complexity(&[0, 0, 0, 1, 1, 1, 0].repeat(2), 2), 7);
+
+    // m-sequences are perfect words, whose linear complexity is the degree
+    for (max_degree, p) in [(10, 2), (5, 3), (3, 5), (2, 7)] {
+        for degree in 1..=max_degree {
+            let mut checker = PerfectnessChecker::new(degree, p);
+            for polynomial in primitive_polynomials(degree, p).unwrap() {
+                let sequence = m_sequence(&polynomial, p).unwrap();
+                assert!(checker.is_perfect(&sequence));
+                assert_eq!(linear_complexity(&sequence.repeat(2), p), degree);
+            }
+        }
+    }
+
+    // Identification among the enumerated cycles
+    for (order, p) in [(2, 2), (3, 2), (4, 2), (5, 2), (2, 3), (1, 5), (1, 7)] {
+        let length = usize::pow(p as usize, order as u32) - 1;
+        let cycles = enum_cycles_fixed_length(length, order, p)
+            .into_iter()
+            .filter(|cycle| is_m_sequence_cycle(cycle, p))
+            .collect::<Vec<_>>();
+        assert_eq!(cycles, m_sequence_cycles(order, p).unwrap());
+    }
+}
@@ -1,8 +1,16 @@
-#![feature(import_trait_associated_functions)]
-
 pub mod count;
+pub mod dbs;
+pub mod dot;
 pub mod r#enum;
+pub mod feedback;
+pub mod johnson;
+pub mod joining;
+pub mod lfsr;
 pub mod lyndon;
 pub mod math;
+#[cfg(feature = "parallel")]
+pub mod parallel;
 pub mod perfect;
+pub mod sample;
+pub mod symmetry;
 pub mod words;
@@ -11,19 +11,47 @@
  * algorithms of Duval [1]. Going from one word to its successor is done in linear
 * time with respect to the size of the underlying array.
 *
+* Conversely, arbitrary words can be decomposed into their (unique)
+* non-increasing sequence of Lyndon factors, following the linear time
+* factorization algorithm of Duval [2]. This readily allows to test whether a
+* word is Lyndon, and to canonicalize words up to rotation.
+*
+* Finally, Lyndon words of fixed length can be ranked and unranked in
+* polynomial time, following the approach of [3]. This allows to jump directly
+* to the i-th Lyndon word, without iterating over the previous ones.
+*
+* Alongside Lyndon words, their periodic counterparts (necklaces) and their
+* prefixes (prenecklaces) are generated with the algorithm of [4]. Lyndon words
+* with a prescribed number of occurrences of each letter are generated with the
+* fixed-content algorithm of [5]. Lyndon bracelets, i.e. Lyndon words that are
+* no larger than the Lyndon rotation of their reversal, are obtained by
+* filtering Lyndon words.
+*
 *
 * Source(s).
 * [1] "Generation d'une section des classes de conjugaison et arbre des mots de
 * Lyndon de longueur bornee", Jean-Pierre DUVAL (1988)
+* [2] "Factorizing words over an ordered alphabet", Jean-Pierre DUVAL (1983)
+* [3] "Computing k-th Lyndon word and generating Lyndon words of fixed length",
+* Tomasz KOCIUMAKA, Jakub RADOSZEWSKI and Wojciech RYTTER (2014)
+* [4] "Necklaces of beads in k colors and k-ary de Bruijn sequences", Harold
+* FREDRICKSEN, Irving J. KESSLER and James MAIORANA (1978)
+* [5] "A fast algorithm to generate necklaces with fixed content", Joe SAWADA
+* (2003)
 *
 **/
+use crate::math::mobius;
 use crate::words::{Letter, Word};
+use num::{BigInt, BigUint};
 
 #[derive(Debug)]
 pub struct LyndonWord {
     vec: Vec<Letter>,
     len: usize,
     max_letter: Letter,
+    // Whether the structure holds the smallest Lyndon word of length
+    // vec.len(), as built by new_smallest (see iter)
+    smallest: bool,
 }
 
 impl LyndonWord {
@@ -31,7 +59,7 @@ impl LyndonWord {
     // the Lyndon word is of sive smaller than the space occupied by the data
     // structure.
     pub fn get_word(&self) -> Word {
-        self.vec[0..self.len as usize].to_vec()
+        self.vec[0..self.len].to_vec()
     }
 
     // Generate the lexicographic smallest Lyndon word on vec.len() characters,
@@ -47,7 +75,106 @@ impl LyndonWord {
             vec,
             len,
             max_letter,
+            smallest: true,
+        }
+    }
+
+    // Build the structure from an existing Lyndon word, e.g. a checkpoint of
+    // an interrupted enumeration, or a bound obtained by unranking. Iteration
+    // starts from that word, whether the length is fixed or upperbounded (in
+    // which case the bound is the length of the word).
+    pub fn from_word(word: &Word, max_letter: Letter) -> Result<Self, String> {
+        if !is_lyndon(word) {
+            return Err(format!("{:?} is not a Lyndon word", word));
+        }
+        if word.iter().any(|&a| a > max_letter) {
+            return Err(format!("{:?} has letters larger than {}", word, max_letter));
+        }
+        Ok(LyndonWord {
+            vec: word.clone(),
+            len: word.len(),
+            max_letter,
+            smallest: false,
+        })
+    }
+
+    // Iterate over the Lyndon words of fixed length, from the current one
+    // (included) to end (excluded, if given). Splitting the enumeration into
+    // such [start, end) intervals allows to share the work.
+    pub fn into_iter_until(self, end: Option<Word>) -> impl Iterator<Item = Word> {
+        self.into_iter(true)
+            .take_while(move |w| end.as_ref().is_none_or(|end| w < end))
+    }
+
+    // Resume an enumeration right after the given checkpoint (excluded), up to
+    // end (excluded, if given)
+    pub fn resume_after(
+        checkpoint: &Word,
+        max_letter: Letter,
+        end: Option<Word>,
+    ) -> Result<impl Iterator<Item = Word>, String> {
+        Ok(LyndonWord::from_word(checkpoint, max_letter)?
+            .into_iter_until(end)
+            .skip(1))
+    }
+
+    // Generate the lexicographic smallest Lyndon word on len characters that
+    // starts with the given prefix, if any. Such a word exists only if the
+    // prefix is a prenecklace, and the smallest prenecklace extending it is its
+    // periodic extension. From there, prenecklaces are visited in
+    // lexicographic order (following the FKM algorithm) until a Lyndon word is
+    // met, or until the prefix gets modified.
+    pub fn new_smallest_with_prefix(prefix: &Word, len: usize, max_letter: Letter) -> Option<Self> {
+        let m = prefix.len();
+        if m == 0 {
+            return Some(LyndonWord::new_smallest(len, max_letter));
+        }
+        if m > len || prefix.iter().any(|&a| a > max_letter) {
+            return None;
         }
+
+        // p is the length of the longest Lyndon prefix of the prenecklace
+        let mut p = 1;
+        for i in 1..m {
+            if prefix[i] < prefix[i - p] {
+                return None;
+            }
+            if prefix[i] > prefix[i - p] {
+                p = i + 1;
+            }
+        }
+
+        let mut vec = prefix.clone();
+        vec.resize(len, 0);
+        for i in m..len {
+            vec[i] = vec[i - p];
+        }
+
+        // A prenecklace is Lyndon iff its longest Lyndon prefix is itself
+        while p != len {
+            let mut i = len;
+            while vec[i - 1] == max_letter {
+                i -= 1;
+                if i == 0 {
+                    return None;
+                }
+            }
+            if i <= m {
+                return None;
+            }
+            vec[i - 1] += 1;
+            p = i;
+            for j in i..len {
+                vec[j] = vec[j - p];
+            }
+        }
+
+        Some(LyndonWord {
+            vec,
+            len,
+            max_letter,
+            smallest: false,
+        })
     }
 
     // We define two families of iterators, that readily correspond to different
@@ -55,9 +182,9 @@ impl LyndonWord {
     // of length beeing fixed by first generated node (B) over Lyndon words of
     // length upperbounded by the first generated node. In the case where (B) is
     // retained, len is changed so that the resulting word is still the
-    // smallest.
+    // smallest (unless the structure holds another word, e.g. a checkpoint).
     pub fn iter(&mut self, fixed_length: bool) -> LyndonWordIter<'_> {
-        if !fixed_length {
+        if !fixed_length && self.smallest {
             self.len = 1;
         }
         LyndonWordIter {
@@ -66,6 +193,29 @@ impl LyndonWord {
             stop_on_next_call: false,
         }
     }
+
+    // An owning counterpart of iter(), for when the iterator has to outlive the
+    // scope in which the Lyndon word was built (e.g. when it is returned as
+    // part of a lazy pipeline).
+    pub fn into_iter(mut self, fixed_length: bool) -> LyndonWordIntoIter {
+        if !fixed_length && self.smallest {
+            self.len = 1;
+        }
+        LyndonWordIntoIter {
+            lw: self,
+            fixed_length,
+            stop_on_next_call: false,
+        }
+    }
+}
+
+// Move the structure to the next Lyndon word, dispatching on the fixed_length
+// flag. An error is returned when the current word was the last one.
+fn next_lw(lw: &mut LyndonWord, fixed_length: bool) -> Result<(), String> {
+    match fixed_length {
+        true => next_fllw(lw),
+        false => next_bllw(lw),
+    }
 }
 
 // The iterator datastructure...
@@ -89,17 +239,32 @@ impl<'a> Iterator for LyndonWordIter<'a> {
 
         // This small trick is the consequence of the first element output of
         // iterator being generated by a call of the .next() method.
-        match self.fixed_length {
-            true => {
-                if let Err(_) = next_fllw(self.lw) {
-                    self.stop_on_next_call = true;
-                }
-            }
-            false => {
-                if let Err(_) = next_bllw(self.lw) {
-                    self.stop_on_next_call = true;
-                }
-            }
+        if next_lw(self.lw, self.fixed_length).is_err() {
+            self.stop_on_next_call = true;
+        }
+
+        Some(word)
+    }
+}
+
+// The owning iterator datastructure...
+pub struct LyndonWordIntoIter {
+    lw: LyndonWord,
+    fixed_length: bool,
+    stop_on_next_call: bool,
+}
+// .. and its implementation, that mirrors the borrowing one
+impl Iterator for LyndonWordIntoIter {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.stop_on_next_call {
+            return None;
+        }
+
+        let word = self.lw.get_word();
+        if next_lw(&mut self.lw, self.fixed_length).is_err() {
+            self.stop_on_next_call = true;
         }
 
         Some(word)
@@ -142,7 +307,7 @@ fn next_fllw(lw: &mut LyndonWord) -> Result<(), String> {
     let mut i = lw.len;
 
     // We add this to handle the case |lw.len|=1
-    if n == 1 && lw.vec[1 - 1] == lw.max_letter {
+    if n == 1 && lw.vec[0] == lw.max_letter {
         return Err("This was the greatest Lyndon word of this fixed length".to_string());
     }
 
@@ -154,7 +319,7 @@ fn next_fllw(lw: &mut LyndonWord) -> Result<(), String> {
 
     // If the first letter is maximal, then the current Lyndon word is
     // already the greatest of its size (only if n>1)
-    if lw.vec[1 - 1] == lw.max_letter && n > 1 {
+    if lw.vec[0] == lw.max_letter && n > 1 {
         return Err("This was the greatest Lyndon word of this fixed length".to_string());
     }
 
@@ -196,6 +361,550 @@ fn next_fllw(lw: &mut LyndonWord) -> Result<(), String> {
     Ok(())
 }
 
+// Chen-Fox-Lyndon factorization of a word, computed with Duval's algorithm.
+// The starting indices of the Lyndon factors are returned, in increasing order
+// (the factors themselves being lexicographically non-increasing).
+pub fn lyndon_factorization_indices(word: &Word) -> Vec<usize> {
+    let n = word.len();
+    let mut starts = Vec::new();
+    let mut i = 0;
+    while i < n {
+        // Extend the current run of (repeated) Lyndon factors as far as possible
+        let mut j = i + 1;
+        let mut k = i;
+        while j < n && word[k] <= word[j] {
+            if word[k] < word[j] {
+                k = i;
+            } else {
+                k += 1;
+            }
+            j += 1;
+        }
+        // Output the repetitions of the factor of length j-k
+        while i <= k {
+            starts.push(i);
+            i += j - k;
+        }
+    }
+    starts
+}
+
+// Same factorization, where the factors are directly given as slices of the
+// original word.
+pub fn lyndon_factorization(word: &Word) -> Vec<&[Letter]> {
+    let mut starts = lyndon_factorization_indices(word);
+    starts.push(word.len());
+    starts.windows(2).map(|w| &word[w[0]..w[1]]).collect()
+}
+
+// A (non-empty) word is Lyndon iff its factorization is made of itself only.
+pub fn is_lyndon(word: &Word) -> bool {
+    !word.is_empty() && lyndon_factorization_indices(word).len() == 1
+}
+
+// Index of the lexicographically smallest rotation of the word (the smallest
+// one if several rotations coincide). Duval's algorithm is run on the word
+// concatenated to itself, that is simulated through modular indexing.
+pub fn least_rotation(word: &Word) -> usize {
+    let n = word.len();
+    let mut i = 0;
+    let mut least = 0;
+    while i < n {
+        least = i;
+        let mut j = i + 1;
+        let mut k = i;
+        while j < 2 * n && word[k % n] <= word[j % n] {
+            if word[k % n] < word[j % n] {
+                k = i;
+            } else {
+                k += 1;
+            }
+            j += 1;
+        }
+        while i <= k {
+            i += j - k;
+        }
+    }
+    least
+}
+
+// The canonical representative of the conjugacy class of a word is its least
+// rotation (i.e. a necklace). For primitive words, this is the unique Lyndon
+// word of the class.
+pub fn conjugacy_class_representative(word: &Word) -> Word {
+    let r = least_rotation(word);
+    [&word[r..], &word[..r]].concat()
+}
+
+// The canonical representative of a word up to rotation and reversal (i.e. of
+// its bracelet) is the least rotation of either the word or its reversal.
+pub fn bracelet_representative(word: &Word) -> Word {
+    let reversed = word.iter().rev().copied().collect::<Word>();
+    conjugacy_class_representative(word).min(conjugacy_class_representative(&reversed))
+}
+
+// A Lyndon bracelet is a Lyndon word that represents its bracelet. Reversal
+// preserving primitivity, this is a Lyndon word no larger than the Lyndon
+// rotation of its reversal.
+pub fn is_lyndon_bracelet(word: &Word) -> bool {
+    is_lyndon(word) && bracelet_representative(word) == *word
+}
+
+// Ranking of Lyndon words of fixed length n in lexicographic order, in the
+// spirit of [3]. The rank of a word w is the number of Lyndon words of length
+// n that are smaller than w. Grouping words by their primitive root, and
+// applying the Mobius inversion formula, it is derived from the numbers, for
+// each divisor e of n, of words x of length e such that all the length-n
+// windows of x^oo are no smaller than w.
+//
+// The latter are counted using an automaton reading x^oo, whose state is the
+// longest suffix read so far that is a prefix of w (i.e. a window that is not
+// yet known to be larger than w). Reading a letter fails as soon as one of the
+// pending windows (the state and its borders) gets smaller than w. The number
+// of such x of length e is then the trace of the e-th power of the transition
+// matrix, as the state reached after reading x^m (for m large enough) only
+// depends on x.
+pub fn rank(word: &Word, sigma: u8) -> BigUint {
+    if word.is_empty() {
+        return BigUint::ZERO;
+    }
+    RankAutomaton::new(word.clone(), sigma).rank()
+}
+
+// The automaton of a word w, as described above. The border of w[0..j) and the
+// transitions from the state j only depend on the prefix w[0..j], so that they
+// are kept when the end of the word is modified.
+struct RankAutomaton {
+    sigma: u8,
+    word: Word,
+    // border[j] is the length of the longest proper border of word[0..j)
+    border: Vec<usize>,
+    // transitions[j][a] is the state reached from j when reading a, if any
+    transitions: Vec<Vec<Option<usize>>>,
+}
+
+impl RankAutomaton {
+    fn new(word: Word, sigma: u8) -> Self {
+        let n = word.len();
+        let mut automaton = RankAutomaton {
+            sigma,
+            word,
+            border: vec![0; n + 1],
+            transitions: vec![Vec::new(); n],
+        };
+        automaton.update_from(0);
+        automaton
+    }
+
+    // Set the letter at position t, and reset the following ones to 0
+    fn set_letter(&mut self, t: usize, letter: Letter) {
+        self.word[t] = letter;
+        self.word[t + 1..].fill(0);
+        self.update_from(t);
+    }
+
+    // Recompute the borders and the transitions that depend on the letters
+    // from position t onwards
+    fn update_from(&mut self, t: usize) {
+        let (n, word, border) = (self.word.len(), &self.word, &mut self.border);
+        for j in usize::max(t + 1, 2)..=n {
+            let mut b = border[j - 1];
+            while b > 0 && word[b] != word[j - 1] {
+                b = border[b];
+            }
+            if word[b] == word[j - 1] {
+                b += 1;
+            }
+            border[j] = b;
+        }
+
+        for j in t..n {
+            self.transitions[j] = (0..self.sigma)
+                .map(|a| {
+                    let mut next = None;
+                    let mut b = j;
+                    loop {
+                        if a < word[b] {
+                            return None;
+                        }
+                        if a == word[b] && b + 1 < n && next.is_none() {
+                            next = Some(b + 1);
+                        }
+                        if b == 0 {
+                            return Some(next.unwrap_or(0));
+                        }
+                        b = border[b];
+                    }
+                })
+                .collect();
+        }
+    }
+
+    fn rank(&self) -> BigUint {
+        let n = self.word.len();
+
+        // traces[e] is the trace of the e-th power of the transition matrix
+        let mut traces = vec![BigUint::ZERO; n + 1];
+        for q in 0..n {
+            let mut walks = vec![BigUint::ZERO; n];
+            walks[q] = BigUint::from(1u32);
+            for trace in traces.iter_mut().skip(1) {
+                let mut next_walks = vec![BigUint::ZERO; n];
+                for (j, nb) in walks.iter().enumerate() {
+                    for &next in self.transitions[j].iter().flatten() {
+                        next_walks[next] += nb;
+                    }
+                }
+                walks = next_walks;
+                *trace += &walks[q];
+            }
+        }
+
+        let mut sum = BigInt::ZERO;
+        for e in (1..=n).filter(|&e| n.is_multiple_of(e)) {
+            let nb_smaller = BigUint::from(self.sigma).pow(e as u32) - &traces[e];
+            sum += BigInt::from(mobius(n / e)) * BigInt::from(nb_smaller);
+        }
+        (sum / n).to_biguint().unwrap()
+    }
+}
+
+// The Lyndon word of length n whose rank is index, if any. It is the largest
+// word whose rank is no larger than index, that is built letter by letter: the
+// rank being nondecreasing with the word, the letter at each position is
+// binary searched, the end of the word being filled with 0. Ranks are thus
+// computed n log(sigma) times, the automaton of the common prefix being kept
+// from one rank to the next.
+//
+// NOTE. Rather than a Word, an Option is returned, that is None when there are
+// no more than index Lyndon words of length n (e.g. when sharding the words by
+// ranges of indices, the last range may end past the last word).
+pub fn unrank(index: &BigUint, n: usize, sigma: u8) -> Option<Word> {
+    if n == 0 {
+        return None;
+    }
+
+    // The rank of the word built so far (i.e. of 0^n at first)
+    let mut automaton = RankAutomaton::new(vec![0; n], sigma);
+    let mut word_rank = BigUint::ZERO;
+    for t in 0..n {
+        // The letter lo is valid, while hi is not
+        let (mut lo, mut hi) = (0, sigma);
+        while hi - lo > 1 {
+            let mid = lo + (hi - lo) / 2;
+            automaton.set_letter(t, mid);
+            let mid_rank = automaton.rank();
+            if mid_rank <= *index {
+                (lo, word_rank) = (mid, mid_rank);
+            } else {
+                hi = mid;
+            }
+        }
+        automaton.set_letter(t, lo);
+    }
+
+    let word = automaton.word;
+    (is_lyndon(&word) && word_rank == *index).then_some(word)
+}
+
+// Necklaces and prenecklaces are generated with the FKM algorithm [4], that
+// visits prenecklaces in lexicographic order. A prenecklace is represented by
+// its letters and the length p of its longest Lyndon prefix; it is a necklace
+// iff p divides its length (and Lyndon iff p is its length). Periodic necklaces
+// are of interest as they correspond to closed walks of the de Bruijn graph
+// that are not simple cycles.
+//
+// As for Lyndon words, iteration is done either (A) over words of length fixed
+// to len (B) over words of length upperbounded by len. In the latter case, the
+// tree of prenecklaces is walked in preorder, which matches the lexicographic
+// order.
+pub struct NecklaceIter {
+    vec: Vec<Letter>,
+    len: usize,
+    p: usize,
+    max_letter: Letter,
+    fixed_length: bool,
+    necklaces_only: bool,
+    stop_on_next_call: bool,
+}
+
+impl NecklaceIter {
+    pub fn necklaces(len: usize, max_letter: Letter, fixed_length: bool) -> Self {
+        NecklaceIter::new(len, max_letter, fixed_length, true)
+    }
+
+    pub fn prenecklaces(len: usize, max_letter: Letter, fixed_length: bool) -> Self {
+        NecklaceIter::new(len, max_letter, fixed_length, false)
+    }
+
+    // The smallest (pre)necklace is 0^len, or 0 when the length is bounded
+    fn new(len: usize, max_letter: Letter, fixed_length: bool, necklaces_only: bool) -> Self {
+        NecklaceIter {
+            vec: vec![0; len],
+            len: if fixed_length { len } else { 1 },
+            p: 1,
+            max_letter,
+            fixed_length,
+            necklaces_only,
+            stop_on_next_call: len == 0,
+        }
+    }
+
+    // Move to the next prenecklace, or return an error if the current one was
+    // the last one.
+    fn next_prenecklace(&mut self) -> Result<(), String> {
+        let n = self.vec.len();
+
+        // In the bounded-length case, go to the first child in the tree
+        if !self.fixed_length && self.len < n {
+            self.vec[self.len] = self.vec[self.len - self.p];
+            self.len += 1;
+            return Ok(());
+        }
+
+        // Remove right trailing max letters, and increase the first non-max
+        // letter. The result is a Lyndon word.
+        let mut i = self.len;
+        while i > 0 && self.vec[i - 1] == self.max_letter {
+            i -= 1;
+        }
+        if i == 0 {
+            return Err("This was the greatest prenecklace".to_string());
+        }
+        self.vec[i - 1] += 1;
+        self.p = i;
+
+        // Either extend it periodically, or stop there
+        if self.fixed_length {
+            for j in i..n {
+                self.vec[j] = self.vec[j - i];
+            }
+        } else {
+            self.len = i;
+        }
+        Ok(())
+    }
+}
+
+impl Iterator for NecklaceIter {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.stop_on_next_call {
+                return None;
+            }
+
+            let is_output = !self.necklaces_only || self.len.is_multiple_of(self.p);
+            let word = self.vec[0..self.len].to_vec();
+            if self.next_prenecklace().is_err() {
+                self.stop_on_next_call = true;
+            }
+
+            if is_output {
+                return Some(word);
+            }
+        }
+    }
+}
+
+// Lyndon bracelets of length fixed to (or upperbounded by) len, in
+// lexicographic order. Each bracelet is checked in linear time, on top of the
+// generation of Lyndon words.
+pub fn lyndon_bracelets(
+    len: usize,
+    max_letter: Letter,
+    fixed_length: bool,
+) -> impl Iterator<Item = Word> {
+    LyndonWord::new_smallest(len, max_letter)
+        .into_iter(fixed_length)
+        .filter(is_lyndon_bracelet)
+}
+
+// Lyndon words with a fixed content, that is with a prescribed number of
+// occurrences of each letter (the content [n_0, n_1, ...] gives words of length
+// n_0 + n_1 + ... over the alphabet [0..content.len())), following the
+// algorithm of [5]. Prenecklaces are extended as in the FKM algorithm [4], with
+// the letters whose budget is not exhausted, and two kinds of nodes are cut so
+// that the generation runs in constant amortized time:
+//   (A) when only the largest letter remains, the single completion of the
+//   prenecklace is directly checked, from the run of largest letters that the
+//   last appended letters are compared to;
+//   (B) when only the smallest letter remains, no completion is Lyndon, as a
+//   Lyndon word does not end with its first letter (unless it is that letter).
+// Words are visited in lexicographic order.
+//
+// In the binary case, Lyndon words of fixed density d (number of 1s) are those
+// of content [n - d, d].
+pub struct FixedContentLyndonWordIter {
+    n: usize,
+    // Remaining number of occurrences of each letter, and the set of letters
+    // whose budget is not exhausted (letters fitting in a u8, in 4 blocks)
+    budget: Vec<usize>,
+    available: [u64; 4],
+    // The smallest and largest letters of the content
+    min_letter: Letter,
+    max_letter: Letter,
+    // The prenecklace under construction, stored from index 1 (index 0 holds a
+    // sentinel 0)
+    word: Word,
+    // For each position of the prenecklace, the length of the longest Lyndon
+    // prefix up to it
+    lyn: Vec<usize>,
+    // For each position, the number of consecutive largest letters ending at
+    // it, and for each position starting a run of largest letters that is
+    // followed by another letter, the length of the run
+    max_run: Vec<usize>,
+    run_len: Vec<usize>,
+    descending: bool,
+}
+
+impl FixedContentLyndonWordIter {
+    pub fn new(content: &[usize]) -> Self {
+        let n = content.iter().sum();
+        let present = (0..content.len()).filter(|&a| content[a] > 0);
+        let mut available = [0; 4];
+        for letter in present.clone() {
+            available[letter / 64] |= 1 << (letter % 64);
+        }
+        FixedContentLyndonWordIter {
+            n,
+            budget: content.to_vec(),
+            available,
+            min_letter: present.clone().min().unwrap_or(0) as Letter,
+            max_letter: present.max().unwrap_or(0) as Letter,
+            word: vec![0; n + 1],
+            lyn: Vec::with_capacity(n),
+            max_run: vec![0; n + 1],
+            run_len: vec![0; n + 1],
+            descending: n > 0,
+        }
+    }
+
+    // The binary Lyndon words of length len with density ones, if any
+    pub fn with_density(len: usize, density: usize) -> Self {
+        match density <= len {
+            true => FixedContentLyndonWordIter::new(&[len - density, density]),
+            false => FixedContentLyndonWordIter::new(&[]),
+        }
+    }
+
+    // The smallest letter, no smaller than min_letter, whose budget is not
+    // exhausted
+    fn next_available(&self, min_letter: usize) -> Option<Letter> {
+        let mut block = min_letter / 64;
+        if block >= self.available.len() {
+            return None;
+        }
+        let mut bits = self.available[block] & (u64::MAX << (min_letter % 64));
+        while bits == 0 {
+            block += 1;
+            if block == self.available.len() {
+                return None;
+            }
+            bits = self.available[block];
+        }
+        Some((block * 64 + bits.trailing_zeros() as usize) as Letter)
+    }
+
+    // Place the smallest available letter, no smaller than min_letter, at the
+    // next position of the prenecklace. Returns false if there is none.
+    fn place_from(&mut self, min_letter: usize) -> bool {
+        let i = self.lyn.len() + 1;
+        let p = self.lyn.last().copied().unwrap_or(1);
+        let base = self.word[i - p];
+        let letter = match i {
+            // A Lyndon word starts with its smallest letter
+            1 => Some(self.min_letter).filter(|&a| a as usize >= min_letter),
+            _ => self.next_available(min_letter.max(base as usize)),
+        };
+        let Some(letter) = letter else {
+            return false;
+        };
+
+        self.budget[letter as usize] -= 1;
+        if self.budget[letter as usize] == 0 {
+            self.available[letter as usize / 64] &= !(1 << (letter % 64));
+        }
+        self.word[i] = letter;
+        self.lyn.push(if letter == base { p } else { i });
+        if letter == self.max_letter {
+            self.max_run[i] = self.max_run[i - 1] + 1;
+        } else {
+            self.max_run[i] = 0;
+            let run = self.max_run[i - 1];
+            self.run_len[i - run] = run;
+        }
+        true
+    }
+
+    // Remove the last letter of the prenecklace, and return it
+    fn pop(&mut self) -> Letter {
+        let i = self.lyn.len();
+        self.lyn.pop();
+        let letter = self.word[i];
+        self.budget[letter as usize] += 1;
+        self.available[letter as usize / 64] |= 1 << (letter % 64);
+        letter
+    }
+
+    // The number of consecutive largest letters starting at position j, that
+    // is assumed not to belong to the trailing run of the prenecklace
+    fn max_run_from(&self, j: usize) -> usize {
+        if self.word[j] != self.max_letter {
+            return 0;
+        }
+        let start = j + 1 - self.max_run[j];
+        self.run_len[start] - (j - start)
+    }
+}
+
+impl Iterator for FixedContentLyndonWordIter {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.descending {
+                let t = self.lyn.len() + 1;
+                let remaining = self.n + 1 - t;
+                let (min, max) = (self.min_letter as usize, self.max_letter as usize);
+                if t > self.n {
+                    self.descending = false;
+                    if self.lyn[self.n - 1] == self.n {
+                        return Some(self.word[1..].to_vec());
+                    }
+                } else if t > 1 && min < max && self.budget[max] == remaining {
+                    // (A) Appending the largest letters, the comparisons with
+                    // the letters one period before stay equalities along the
+                    // run starting at t - p. Since the prenecklace starts with
+                    // a smaller letter, it becomes Lyndon as soon as they stop.
+                    self.descending = false;
+                    let p = self.lyn[t - 2];
+                    if self.max_run_from(t - p) < remaining {
+                        let mut word = self.word[1..t].to_vec();
+                        word.resize(self.n, self.max_letter);
+                        return Some(word);
+                    }
+                } else if t > 1 && min < max && self.budget[min] == remaining {
+                    // (B)
+                    self.descending = false;
+                } else if !self.place_from(0) {
+                    self.descending = false;
+                }
+            } else {
+                // Move to the next sibling of the deepest node, if any
+                if self.lyn.is_empty() {
+                    return None;
+                }
+                let letter = self.pop();
+                if self.place_from(letter as usize + 1) {
+                    self.descending = true;
+                }
+            }
+        }
+    }
+}
+
 //
 //
 //
@@ -290,3 +999,278 @@ fn test_bounded_length_lw_iterator() {
     let mut lw = LyndonWord::new_smallest(1, 2);
     assert_eq!(lw.iter(false).collect::<Vec<_>>(), bllw12);
 }
+
+#[test]
+fn test_owning_lw_iterator() {
+    for fixed_length in [true, false] {
+        let mut lw = LyndonWord::new_smallest(5, 2);
+        let borrowed = lw.iter(fixed_length).collect::<Vec<_>>();
+        let owned = LyndonWord::new_smallest(5, 2)
+            .into_iter(fixed_length)
+            .collect::<Vec<_>>();
+        assert_eq!(borrowed, owned);
+    }
+}
+
+#[test]
+fn test_lyndon_factorization() {
+    let word = vec![1, 0, 1, 1, 0, 1, 0, 0, 1, 0];
+    assert_eq!(lyndon_factorization_indices(&word), [0, 1, 4, 6, 9]);
+    assert_eq!(
+        lyndon_factorization(&word),
+        [&[1][..], &[0, 1, 1], &[0, 1], &[0, 0, 1], &[0]]
+    );
+    assert_eq!(lyndon_factorization(&vec![0, 0, 0]), [[0], [0], [0]]);
+    assert!(lyndon_factorization(&vec![]).is_empty());
+}
+
+#[test]
+fn test_is_lyndon() {
+    // Lyndon words of length 4 over {0, 1, 2} are exactly the 18 output of the
+    // fixed-length iterator
+    let mut nb_lyndon = 0;
+    for code in 0..81 {
+        let word = (0..4).map(|i| (code / u8::pow(3, i)) % 3).collect::<Word>();
+        if is_lyndon(&word) {
+            nb_lyndon += 1;
+        }
+    }
+    assert_eq!(nb_lyndon, 18);
+    assert!(
+        LyndonWord::new_smallest(4, 2)
+            .into_iter(true)
+            .all(|w| is_lyndon(&w))
+    );
+    assert!(!is_lyndon(&vec![0, 1, 0, 1]));
+    assert!(!is_lyndon(&vec![1, 0]));
+    assert!(!is_lyndon(&vec![]));
+}
+
+#[test]
+fn test_least_rotation() {
+    assert_eq!(least_rotation(&vec![1, 1, 0, 1, 0]), 2);
+    assert_eq!(least_rotation(&vec![0, 1, 0, 1]), 0);
+    assert_eq!(least_rotation(&vec![1, 0, 1, 0]), 1);
+    assert_eq!(least_rotation(&vec![2, 2, 2]), 0);
+    assert_eq!(
+        conjugacy_class_representative(&vec![1, 2, 0, 1, 1]),
+        [0, 1, 1, 1, 2]
+    );
+    assert_eq!(
+        conjugacy_class_representative(&vec![1, 0, 1, 0]),
+        [0, 1, 0, 1]
+    );
+}
+
+#[test]
+fn test_smallest_with_prefix() {
+    let lw = LyndonWord::new_smallest_with_prefix(&vec![0, 1], 5, 1).unwrap();
+    assert_eq!(lw.get_word(), [0, 1, 0, 1, 1]);
+    let lw = LyndonWord::new_smallest_with_prefix(&vec![0, 1, 1], 4, 1).unwrap();
+    assert_eq!(lw.get_word(), [0, 1, 1, 1]);
+    let lw = LyndonWord::new_smallest_with_prefix(&vec![2], 1, 2).unwrap();
+    assert_eq!(lw.get_word(), [2]);
+
+    // Prefixes that are not prenecklaces, or with no Lyndon extension
+    assert!(LyndonWord::new_smallest_with_prefix(&vec![1, 0], 4, 1).is_none());
+    assert!(LyndonWord::new_smallest_with_prefix(&vec![1, 1], 3, 1).is_none());
+    assert!(LyndonWord::new_smallest_with_prefix(&vec![0, 1, 0], 3, 1).is_none());
+
+    // Splitting along all prefixes of length 2 partitions the Lyndon words
+    let mut lw = LyndonWord::new_smallest(6, 2);
+    let all = lw.iter(true).collect::<Vec<_>>();
+    let mut split = Vec::new();
+    for a in 0..3 {
+        for b in 0..3 {
+            let prefix = vec![a, b];
+            if let Some(lw) = LyndonWord::new_smallest_with_prefix(&prefix, 6, 2) {
+                split.extend(lw.into_iter(true).take_while(|w| w[..2] == prefix));
+            }
+        }
+    }
+    assert_eq!(all, split);
+}
+
+#[test]
+fn test_rank_unrank() {
+    for (n, sigma) in [(1, 3), (4, 3), (6, 2), (8, 2), (5, 4)] {
+        let lws = LyndonWord::new_smallest(n, sigma - 1).into_iter(true);
+        for (i, lw) in lws.enumerate() {
+            assert_eq!(rank(&lw, sigma), BigUint::from(i));
+            assert_eq!(unrank(&BigUint::from(i), n, sigma), Some(lw));
+        }
+    }
+
+    // Non-Lyndon words are ranked among Lyndon words, but never unranked
+    assert_eq!(rank(&vec![0, 1, 0, 1], 2), BigUint::from(2u32));
+    assert_eq!(rank(&vec![1, 1, 1, 1], 2), BigUint::from(3u32));
+    assert_eq!(unrank(&BigUint::from(3u32), 4, 2), None);
+
+    // The last Lyndon word of length 20 over {0, 1}, out of 52377
+    let last = unrank(&BigUint::from(52376u32), 20, 2).unwrap();
+    assert_eq!(last, [[0].as_slice(), &[1; 19]].concat());
+    assert_eq!(unrank(&BigUint::from(52377u32), 20, 2), None);
+    let lw = unrank(&BigUint::from(31415u32), 20, 2).unwrap();
+    assert!(is_lyndon(&lw));
+    assert_eq!(rank(&lw, 2), BigUint::from(31415u32));
+}
+
+#[test]
+fn test_seekable_iteration() {
+    let mut lw = LyndonWord::new_smallest(4, 2);
+    let fllw42 = lw.iter(true).collect::<Vec<_>>();
+
+    // Resuming from any checkpoint
+    for (i, checkpoint) in fllw42.iter().enumerate() {
+        let resumed = LyndonWord::resume_after(checkpoint, 2, None).unwrap();
+        assert_eq!(resumed.collect::<Vec<_>>(), fllw42[i + 1..]);
+    }
+
+    // Splitting into [start, end) intervals
+    let interval = LyndonWord::from_word(&fllw42[3], 2)
+        .unwrap()
+        .into_iter_until(Some(fllw42[7].clone()));
+    assert_eq!(interval.collect::<Vec<_>>(), fllw42[3..7]);
+    let resumed = LyndonWord::resume_after(&fllw42[3], 2, Some(fllw42[7].clone())).unwrap();
+    assert_eq!(resumed.collect::<Vec<_>>(), fllw42[4..7]);
+
+    // Intervals given by indices, through unranking
+    let (start, end) = (BigUint::from(3000u32), BigUint::from(3010u32));
+    let interval = LyndonWord::from_word(&unrank(&start, 16, 2).unwrap(), 1)
+        .unwrap()
+        .into_iter_until(unrank(&end, 16, 2));
+    let expected = (3000..3010u32).map(|i| unrank(&BigUint::from(i), 16, 2).unwrap());
+    assert!(interval.eq(expected));
+
+    // Resuming a bounded-length enumeration, up to the length of the
+    // checkpoint
+    let bllw4 = LyndonWord::new_smallest(4, 1)
+        .into_iter(false)
+        .collect::<Vec<_>>();
+    for (i, checkpoint) in bllw4.iter().enumerate().filter(|(_, w)| w.len() == 4) {
+        let resumed = LyndonWord::from_word(checkpoint, 1)
+            .unwrap()
+            .into_iter(false);
+        assert_eq!(resumed.collect::<Vec<_>>(), bllw4[i..]);
+    }
+
+    assert!(LyndonWord::from_word(&vec![0, 1, 0, 1], 1).is_err());
+    assert!(LyndonWord::from_word(&vec![0, 2], 1).is_err());
+}
+
+#[test]
+fn test_necklace_iterators() {
+    use crate::words::all_words;
+
+    // Brute force definitions, on all words of length up to 5 over {0, 1, 2}
+    let is_necklace = |w: &Word| conjugacy_class_representative(w) == *w;
+    let is_prenecklace = |w: &Word| {
+        all_words(w.len(), 3)
+            .iter()
+            .any(|x| is_necklace(&[&w[..], x].concat()))
+    };
+
+    let mut bounded_necklaces = Vec::new();
+    let mut bounded_prenecklaces = Vec::new();
+    for n in 1..=5 {
+        let necklaces = all_words(n, 3).into_iter().filter(is_necklace);
+        let necklaces = necklaces.collect::<Vec<_>>();
+        assert_eq!(
+            NecklaceIter::necklaces(n, 2, true).collect::<Vec<_>>(),
+            necklaces
+        );
+        let prenecklaces = all_words(n, 3).into_iter().filter(is_prenecklace);
+        let prenecklaces = prenecklaces.collect::<Vec<_>>();
+        assert_eq!(
+            NecklaceIter::prenecklaces(n, 2, true).collect::<Vec<_>>(),
+            prenecklaces
+        );
+        bounded_necklaces.extend(necklaces);
+        bounded_prenecklaces.extend(prenecklaces);
+    }
+    bounded_necklaces.sort();
+    bounded_prenecklaces.sort();
+    assert_eq!(
+        NecklaceIter::necklaces(5, 2, false).collect::<Vec<_>>(),
+        bounded_necklaces
+    );
+    assert_eq!(
+        NecklaceIter::prenecklaces(5, 2, false).collect::<Vec<_>>(),
+        bounded_prenecklaces
+    );
+
+    // OEIS's A000031
+    let a000031 = [1, 2, 3, 4, 6, 8, 14, 20, 36, 60, 108];
+    for (n, &expected) in a000031.iter().enumerate().skip(1) {
+        assert_eq!(NecklaceIter::necklaces(n, 1, true).count(), expected);
+    }
+}
+
+#[test]
+fn test_fixed_content_iterator() {
+    // Compare with the filtering of all Lyndon words of the same length
+    let content_of = |w: &Word, sigma: usize| {
+        let mut content = vec![0; sigma];
+        w.iter().for_each(|&a| content[a as usize] += 1);
+        content
+    };
+    for content in [
+        vec![3, 3],
+        vec![4, 2],
+        vec![1, 5],
+        vec![2, 2, 2],
+        vec![0, 3, 2],
+        vec![2, 0, 1],
+        vec![1, 0],
+        vec![0, 3],
+    ] {
+        let n = content.iter().sum();
+        let expected = LyndonWord::new_smallest(n, content.len() as Letter - 1)
+            .into_iter(true)
+            .filter(|w| content_of(w, content.len()) == content)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            FixedContentLyndonWordIter::new(&content).collect::<Vec<_>>(),
+            expected
+        );
+    }
+    assert_eq!(FixedContentLyndonWordIter::new(&[0, 0]).next(), None);
+    assert_eq!(
+        FixedContentLyndonWordIter::new(&[0, 0, 1]).collect::<Vec<_>>(),
+        [[2]]
+    );
+
+    // Large alphabets, whose letters span several blocks
+    let mut content = vec![0; 200];
+    content[3] = 2;
+    content[70] = 1;
+    content[199] = 2;
+    assert_eq!(FixedContentLyndonWordIter::new(&content).count(), 6);
+
+    // Binary Lyndon words of length 12 and density 6 (OEIS's A022553)
+    assert_eq!(FixedContentLyndonWordIter::with_density(12, 6).count(), 75);
+    assert_eq!(FixedContentLyndonWordIter::with_density(3, 4).next(), None);
+}
+
+#[test]
+fn test_lyndon_bracelets() {
+    assert_eq!(
+        bracelet_representative(&vec![1, 0, 1, 1, 0, 0]),
+        [0, 0, 1, 0, 1, 1]
+    );
+    assert_eq!(
+        bracelet_representative(&vec![0, 0, 1, 1, 0, 1]),
+        [0, 0, 1, 0, 1, 1]
+    );
+    assert!(is_lyndon_bracelet(&vec![0, 0, 1, 0, 1, 1]));
+    assert!(!is_lyndon_bracelet(&vec![0, 0, 1, 1, 0, 1]));
+    assert!(!is_lyndon_bracelet(&vec![0, 1, 0, 1]));
+
+    // OEIS's A001371
+    let a001371 = [1, 2, 1, 2, 3, 6, 8, 16, 24, 42, 69];
+    for (n, &expected) in a001371.iter().enumerate().skip(1) {
+        assert_eq!(lyndon_bracelets(n, 1, true).count(), expected);
+    }
+    assert_eq!(lyndon_bracelets(10, 1, false).count(), 173);
+    assert!(lyndon_bracelets(6, 2, false).is_sorted());
+}
@@ -9,16 +9,61 @@
 * - dbg_cycles count [PARAMS], is used to count the cycles
 * - dbg_cycles enum  [PARAMS], is used to enumerate them the cycles
 * - dbg_cycles conjecture    , is used to assess the conjecture on the (few) values that are tractable
+* - dbg_cycles export [PARAMS], is used to draw the graph (in the DOT format), highlighting some cycles
+* - dbg_cycles sample [PARAMS], is used to draw cycles of a given length uniformly at random
+* - dbg_cycles dbs [PARAMS], is used to generate a de Bruijn sequence (i.e. a Hamiltonian cycle)
+* - dbg_cycles feedback [PARAMS], is used to find the cycles of a feedback shift register
 *
 * The binary alphabet is consider by default. If no length parameter is set,
-* then the research will be carried for all cycles
+* then the research will be carried for all cycles. Results of count and enum
+* can be output as machine-readable records (JSON, NDJSON or CSV) instead of
+* prose, using the --format option. They can also be restricted to the cycles
+* with a given content (number of occurrences of each letter), using the
+* --content option, or be carried out up to some symmetries of the graph
+* (reversal, permutations of the alphabet, or both), using the --up-to option,
+* or to the cycles going through a given vertex, using the --through option,
+* or to the cycles avoiding some vertices and edges listed in a file, using the
+* --forbidden option.
+*
+* Cycles can also be enumerated (and counted) with Johnson's algorithm on the
+* explicit graph, rather than through perfect Lyndon words, using the --method
+* option. This is much slower, but allows to double check the results.
 *
 **/
-use clap::{Parser, Subcommand};
+use clap::{Parser, Subcommand, ValueEnum};
 use colored::Colorize;
-use dbg_cycles::count::count_cycles_only_enum;
-use dbg_cycles::count::{count_cycles_with_formula, Count};
-use dbg_cycles::r#enum::{enum_cycles_bounded_length, enum_cycles_fixed_length};
+use dbg_cycles::count::{
+    Count, count_cycles_avoiding, count_cycles_fixed_content, count_cycles_through,
+    count_cycles_with_formula,
+};
+use dbg_cycles::dbs::{Construction, de_bruijn_sequence};
+use dbg_cycles::dot::{dbg_to_dot, word_label};
+use dbg_cycles::r#enum::{
+    enum_cycles_avoiding, enum_cycles_fixed_content, enum_cycles_through, map_cycle_to_word,
+};
+use dbg_cycles::feedback::FeedbackFunction;
+use dbg_cycles::johnson::johnson_cycles;
+use dbg_cycles::perfect::ForbiddenKmers;
+use dbg_cycles::sample::CycleSampler;
+use dbg_cycles::symmetry::{SymmetryGroup, count_cycle_orbits, enum_cycle_orbits};
+use dbg_cycles::words::{Cycle, Word, cycle_length};
+use num::BigUint;
+use std::path::PathBuf;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// When built with the "parallel" feature, the enumeration-based functions are
+// swapped for their multi-threaded counterparts.
+#[cfg(feature = "parallel")]
+use dbg_cycles::parallel::{
+    par_count_cycles_only_enum as count_cycles_only_enum,
+    par_enum_cycles_bounded_length as enum_cycles_bounded_length,
+    par_enum_cycles_fixed_length as enum_cycles_fixed_length,
+};
+#[cfg(not(feature = "parallel"))]
+use dbg_cycles::{
+    count::count_cycles_only_enum,
+    r#enum::{enum_cycles_bounded_length, enum_cycles_fixed_length},
+};
 
 //
 // We rely on the clap crate for parsing arguments and displaying help messages.
@@ -46,7 +91,6 @@ use dbg_cycles::r#enum::{enum_cycles_bounded_length, enum_cycles_fixed_length};
 "#,
     version = "1.0"
 )]
-
 struct Args {
     #[command(subcommand)]
     command: Commands,
@@ -57,7 +101,7 @@ enum Commands {
     /// Count simple cycles of the de Bruijn graph.
     Count {
         /// Order of the de Bruijn graph
-        #[arg(short = 'k', long)]
+        #[arg(short = 'k', long, value_parser = parse_order)]
         order: usize,
         /// Length of the cycles
         #[arg(short = 'l', long, default_value_t = 0)]
@@ -65,12 +109,31 @@ enum Commands {
         /// Size of the alphabet
         #[arg(short = 's', long, default_value_t = 2)]
         sigma: u8,
+        /// Number of occurrences of each letter (e.g. 3,2), setting both the length and the alphabet
+        #[arg(short = 'c', long, value_delimiter = ',', conflicts_with_all = ["length", "sigma"])]
+        content: Vec<usize>,
+        /// Only consider one cycle per orbit of isomorphic cycles
+        #[arg(long, value_enum, conflicts_with = "content")]
+        up_to: Option<Symmetry>,
+        /// Only consider the cycles going through this vertex (e.g. 0.1.1)
+        #[arg(short = 't', long, value_parser = parse_word, conflicts_with_all = ["content", "up_to"])]
+        through: Option<Word>,
+        /// Only consider the cycles avoiding the vertices and edges listed in this file (one per line, e.g. 0.0.0)
+        #[arg(long, conflicts_with_all = ["content", "up_to", "through"])]
+        forbidden: Option<PathBuf>,
+        /// Algorithm used to find the cycles (only for unrestricted cycles)
+        #[arg(short = 'm', long, value_enum, default_value_t = Method::Lyndon)]
+        #[arg(conflicts_with_all = ["content", "up_to", "through", "forbidden"])]
+        method: Method,
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value_t = Format::Text)]
+        format: Format,
     },
 
     /// Enumerate simple cycles of the de Bruijn graph (of length no larger than the order)
     Enum {
         /// Order of the de Bruijn graph
-        #[arg(short = 'k', long)]
+        #[arg(short = 'k', long, value_parser = parse_order)]
         order: usize,
         /// Length of the cycles
         #[arg(short = 'l', long, default_value_t = 0)]
@@ -78,10 +141,176 @@ enum Commands {
         /// Size of the alphabet
         #[arg(short = 's', long, default_value_t = 2)]
         sigma: u8,
+        /// Number of occurrences of each letter (e.g. 3,2), setting both the length and the alphabet
+        #[arg(short = 'c', long, value_delimiter = ',', conflicts_with_all = ["length", "sigma"])]
+        content: Vec<usize>,
+        /// Only consider one cycle per orbit of isomorphic cycles
+        #[arg(long, value_enum, conflicts_with = "content")]
+        up_to: Option<Symmetry>,
+        /// Only consider the cycles going through this vertex (e.g. 0.1.1)
+        #[arg(short = 't', long, value_parser = parse_word, conflicts_with_all = ["content", "up_to"])]
+        through: Option<Word>,
+        /// Only consider the cycles avoiding the vertices and edges listed in this file (one per line, e.g. 0.0.0)
+        #[arg(long, conflicts_with_all = ["content", "up_to", "through"])]
+        forbidden: Option<PathBuf>,
+        /// Algorithm used to find the cycles (only for unrestricted cycles)
+        #[arg(short = 'm', long, value_enum, default_value_t = Method::Lyndon)]
+        #[arg(conflicts_with_all = ["content", "up_to", "through", "forbidden"])]
+        method: Method,
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value_t = Format::Text)]
+        format: Format,
     },
 
     /// Test the conjecture by comparing the result obtain with enumeration
     Conjecture,
+
+    /// Export the de Bruijn graph in the DOT format of Graphviz
+    Export {
+        /// Order of the de Bruijn graph
+        #[arg(short = 'k', long, value_parser = parse_order)]
+        order: usize,
+        /// Highlight the simple cycles of this length
+        #[arg(short = 'l', long, default_value_t = 0)]
+        length: usize,
+        /// Size of the alphabet
+        #[arg(short = 's', long, default_value_t = 2)]
+        sigma: u8,
+        /// Only highlight the cycles of these indices (in enumeration order, from 0)
+        #[arg(short = 'i', long = "index")]
+        indices: Vec<usize>,
+    },
+
+    /// Sample simple cycles of the de Bruijn graph uniformly at random
+    Sample {
+        /// Order of the de Bruijn graph
+        #[arg(short = 'k', long, value_parser = parse_order)]
+        order: usize,
+        /// Length of the cycles
+        #[arg(short = 'l', long)]
+        length: usize,
+        /// Size of the alphabet
+        #[arg(short = 's', long, default_value_t = 2)]
+        sigma: u8,
+        /// Number of cycles to sample
+        #[arg(short = 'n', long, default_value_t = 1)]
+        number: usize,
+        /// Seed of the random generator (drawn from the clock if absent)
+        #[arg(long)]
+        seed: Option<u64>,
+        /// Number of words drawn for each cycle before giving up
+        #[arg(long, default_value_t = 1_000_000)]
+        max_trials: u64,
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Generate a de Bruijn sequence, i.e. a simple cycle through all the vertices
+    Dbs {
+        /// Order of the de Bruijn graph
+        #[arg(short = 'k', long, value_parser = parse_order)]
+        order: usize,
+        /// Size of the alphabet
+        #[arg(short = 's', long, default_value_t = 2)]
+        sigma: u8,
+        /// Construction of the sequence
+        #[arg(short = 'c', long, value_enum, default_value_t = DbsConstruction::Granddaddy)]
+        construction: DbsConstruction,
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+
+    /// Decompose the state graph of a binary feedback shift register into cycles
+    Feedback {
+        /// Order of the de Bruijn graph (number of bits of the register)
+        #[arg(short = 'k', long, value_parser = parse_order)]
+        order: usize,
+        /// File containing the truth table of the feedback function (2^k bits, e.g. 01011010)
+        truth_table: PathBuf,
+        /// Output format
+        #[arg(short = 'f', long, value_enum, default_value_t = Format::Text)]
+        format: Format,
+    },
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Format {
+    /// Human-readable sentences
+    Text,
+    /// A single JSON array of records
+    Json,
+    /// One JSON record per line
+    Ndjson,
+    /// Comma-separated values, with a header line
+    Csv,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Symmetry {
+    /// A cycle and its reversed traversal (in the reversed graph)
+    Reversal,
+    /// Permutations of the alphabet (e.g. complement)
+    Permutation,
+    /// Both permutations of the alphabet and reversal
+    PermutationReversal,
+}
+
+impl Symmetry {
+    // How the symmetry is referred to in the prose output
+    fn describe(&self) -> &'static str {
+        match self {
+            Symmetry::Reversal => "reversal",
+            Symmetry::Permutation => "permutations of the alphabet",
+            Symmetry::PermutationReversal => "permutations of the alphabet and reversal",
+        }
+    }
+
+    fn group(&self, sigma: u8) -> SymmetryGroup {
+        match self {
+            Symmetry::Reversal => SymmetryGroup::new(sigma, false, true),
+            Symmetry::Permutation => SymmetryGroup::new(sigma, true, false),
+            Symmetry::PermutationReversal => SymmetryGroup::new(sigma, true, true),
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum Method {
+    /// Generation of perfect Lyndon words
+    Lyndon,
+    /// Johnson's algorithm on the explicit graph (much slower)
+    Johnson,
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq)]
+enum DbsConstruction {
+    /// Concatenation of the Lyndon words whose length divides the order
+    Granddaddy,
+    /// Greedy, appending the largest possible symbol (prefer-one)
+    PreferLargest,
+    /// Greedy, repeating the last symbol when possible
+    PreferSame,
+    /// Successor rule of the grandmama sequence (binary only)
+    Grandmama,
+    /// Successor rule of the cool-lex sequence (binary only)
+    CoolLex,
+    /// Joining of the cycles of the pure cycling register (the necklaces)
+    CycleJoining,
+}
+
+impl DbsConstruction {
+    fn construction(&self) -> Construction {
+        match self {
+            DbsConstruction::Granddaddy => Construction::Granddaddy,
+            DbsConstruction::PreferLargest => Construction::PreferLargest,
+            DbsConstruction::PreferSame => Construction::PreferSame,
+            DbsConstruction::Grandmama => Construction::Grandmama,
+            DbsConstruction::CoolLex => Construction::CoolLex,
+            DbsConstruction::CycleJoining => Construction::CycleJoining,
+        }
+    }
 }
 
 fn main() {
@@ -92,17 +321,79 @@ fn main() {
             order,
             length,
             sigma,
-        } => {
-            cli_count(*length, *order, *sigma);
-        }
+            content,
+            up_to,
+            through,
+            forbidden,
+            method,
+            format,
+        } => match (format, up_to, through, forbidden) {
+            _ if *method == Method::Johnson => cli_count_johnson(*length, *order, *sigma, *format),
+            _ if !content.is_empty() => cli_count_fixed_content(content, *order, *format),
+            (_, _, _, Some(path)) => cli_count_avoiding(path, *length, *order, *sigma, *format),
+            (_, _, Some(vertex), _) => cli_count_through(vertex, *length, *order, *sigma, *format),
+            (_, Some(symmetry), _, _) => {
+                cli_count_up_to(*length, *order, *sigma, *symmetry, *format)
+            }
+            (Format::Text, None, None, None) => cli_count(*length, *order, *sigma),
+            _ => cli_count_records(*length, *order, *sigma, *format),
+        },
         Commands::Enum {
             order,
             length,
             sigma,
-        } => {
-            cli_enum(*length, *order, *sigma);
-        }
+            content,
+            up_to,
+            through,
+            forbidden,
+            method,
+            format,
+        } => match (format, up_to, through, forbidden) {
+            _ if *method == Method::Johnson => cli_enum_johnson(*length, *order, *sigma, *format),
+            _ if !content.is_empty() => cli_enum_fixed_content(content, *order, *format),
+            (_, _, _, Some(path)) => cli_enum_avoiding(path, *length, *order, *sigma, *format),
+            (_, _, Some(vertex), _) => cli_enum_through(vertex, *length, *order, *sigma, *format),
+            (_, Some(symmetry), _, _) => {
+                cli_enum_up_to(*length, *order, *sigma, *symmetry, *format)
+            }
+            (Format::Text, None, None, None) => cli_enum(*length, *order, *sigma),
+            _ => cli_enum_records(*length, *order, *sigma, *format),
+        },
         Commands::Conjecture => cli_test_conjecture_plusthree(),
+        Commands::Export {
+            order,
+            length,
+            sigma,
+            indices,
+        } => cli_export(*length, *order, *sigma, indices),
+        Commands::Sample {
+            order,
+            length,
+            sigma,
+            number,
+            seed,
+            max_trials,
+            format,
+        } => cli_sample(
+            *length,
+            *order,
+            *sigma,
+            *number,
+            *seed,
+            *max_trials,
+            *format,
+        ),
+        Commands::Dbs {
+            order,
+            sigma,
+            construction,
+            format,
+        } => cli_dbs(*order, *sigma, *construction, *format),
+        Commands::Feedback {
+            order,
+            truth_table,
+            format,
+        } => cli_feedback(truth_table, *order, *format),
     }
 }
 
@@ -111,7 +402,7 @@ fn main() {
 fn cli_count(length: usize, order: usize, sigma: u8) {
     if length != 0 {
         let count = count_cycles_with_formula(length, order, sigma, false);
-        let mut answer = 0;
+        let mut answer = BigUint::ZERO;
         let mut status = "dummy".purple();
         match count {
             Count::FromProvedFormula(x) => {
@@ -128,18 +419,15 @@ fn cli_count(length: usize, order: usize, sigma: u8) {
             }
             _ => (),
         }
-        println!("There are {} simple cycles of length {} in the deBruijn graph of order {} over the [0..{}) alphabet ({})",
-                 answer,
-                 length,
-                 order,
-                 sigma,
-                 status,
+        println!(
+            "There are {} simple cycles of length {} in the deBruijn graph of order {} over the [0..{}) alphabet ({})",
+            answer, length, order, sigma, status,
         )
     } else {
         println!("Within dBG({}, {}), one can find...\n", order, sigma);
         for l in 1..=usize::pow(sigma as usize, order as u32) {
             let count = count_cycles_with_formula(l, order, sigma, false);
-            let mut answer = 0;
+            let mut answer = BigUint::ZERO;
             let mut status = "dummy".purple();
             match count {
                 Count::FromProvedFormula(x) => {
@@ -192,6 +480,12 @@ fn cli_test_conjecture_plusthree() {
     }
 }
 
+// Print a cycle on a single line, as a sequence of dot-separated k-mers
+fn print_cycle(cycle: &Cycle) {
+    let kmers = cycle.iter().map(word_label).collect::<Vec<_>>();
+    println!("  {}", kmers.join(" --> "));
+}
+
 // Pretty print the simple cycles in the dbg. When no length is given, the
 // cycles are generated using the bounded-length iterator on Lyndon words, and
 // then sorted/grouped by cycle size. We highlight where this number comes from
@@ -207,20 +501,8 @@ fn cli_enum(length: usize, order: usize, sigma: u8) {
             order,
             sigma
         );
-        for (_, cycle) in cycles.iter().enumerate() {
-            print!("  ");
-            for (j, word) in cycle.iter().enumerate() {
-                if j != 0 {
-                    print!(" --> ");
-                }
-                for (k, letter) in word.iter().enumerate() {
-                    if k != 0 {
-                        print!(".");
-                    }
-                    print!("{}", letter);
-                }
-            }
-            println!();
+        for cycle in cycles.iter() {
+            print_cycle(cycle);
         }
     } else {
         let max_cycle_length = usize::pow(sigma as usize, order as u32);
@@ -228,24 +510,611 @@ fn cli_enum(length: usize, order: usize, sigma: u8) {
         cycles.sort_by_key(|x| (x.len(), x.clone()));
         let mut current_len = 0;
         println!("In the de Bruijn graph dBG({}, {})...", order, sigma);
-        for (_, cycle) in cycles.iter().enumerate() {
-            if current_len != cycle.len() {
-                current_len = cycle.len();
+        for cycle in cycles.iter() {
+            if current_len != cycle_length(cycle) {
+                current_len = cycle_length(cycle);
                 println!("\n..the simple cycles of length {}", current_len);
             }
-            print!("  ");
-            for (j, word) in cycle.iter().enumerate() {
-                if j != 0 {
-                    print!(" --> ");
-                }
-                for (k, letter) in word.iter().enumerate() {
-                    if k != 0 {
-                        print!(".");
-                    }
-                    print!("{}", letter);
-                }
+            print_cycle(cycle);
+        }
+    }
+}
+
+// Count the simple cycles with a given content, in any format
+fn cli_count_fixed_content(content: &[usize], order: usize, format: Format) {
+    let (length, sigma) = (content.iter().sum::<usize>(), content.len());
+    let count = count_cycles_fixed_content(content, order);
+
+    if format == Format::Text {
+        println!(
+            "There are {} simple cycles with content {:?} in the deBruijn graph of order {} over the [0..{}) alphabet ({})",
+            count.to_option().unwrap_or_default(),
+            content,
+            order,
+            sigma,
+            "computed".blue(),
+        );
+    } else {
+        let record = vec![
+            Field::Number(order.to_string()),
+            Field::Number(sigma.to_string()),
+            Field::Number(length.to_string()),
+            count_field(&count),
+            Field::Text(provenance(&count).to_string()),
+        ];
+        print_records(
+            format,
+            &["order", "sigma", "length", "count", "provenance"],
+            std::iter::once(record),
+        );
+    }
+}
+
+// Enumerate the simple cycles with a given content, in any format
+fn cli_enum_fixed_content(content: &[usize], order: usize, format: Format) {
+    let sigma = content.len() as u8;
+    let cycles = enum_cycles_fixed_content(content, order);
+
+    if format == Format::Text {
+        println!(
+            "The {} simple cycles with content {:?} in dBG({}, {}) are",
+            cycles.len(),
+            content,
+            order,
+            sigma
+        );
+        for cycle in cycles.iter() {
+            print_cycle(cycle);
+        }
+    } else {
+        let records = cycles
+            .into_iter()
+            .map(|cycle| cycle_record(order, sigma, cycle));
+        print_records(format, CYCLE_FIELDS, records);
+    }
+}
+
+// The lengths considered by the CLI: the given one, or all of them if none is
+// given (i.e. 0)
+fn lengths(length: usize, order: usize, sigma: u8) -> std::ops::RangeInclusive<usize> {
+    match length {
+        0 => 1..=usize::pow(sigma as usize, order as u32),
+        _ => length..=length,
+    }
+}
+
+// Print the numbers of simple cycles (or of classes of cycles, whose name is
+// given), one per length, among those satisfying some restriction (e.g.
+// "through the vertex 0.1.1"), in any format
+fn print_restricted_counts(
+    order: usize,
+    sigma: u8,
+    restriction: &str,
+    name: &str,
+    counts: impl Iterator<Item = (usize, Count)>,
+    format: Format,
+) {
+    if format == Format::Text {
+        println!(
+            "Within dBG({}, {}), {}, one can find...\n",
+            order, sigma, restriction
+        );
+        for (l, count) in counts {
+            println!(
+                "...{} of length {}:\t{}\t({})",
+                name,
+                l,
+                count.to_option().unwrap_or_default(),
+                provenance(&count).blue(),
+            );
+        }
+    } else {
+        let records = counts.map(|(l, count)| {
+            vec![
+                Field::Number(order.to_string()),
+                Field::Number(sigma.to_string()),
+                Field::Number(l.to_string()),
+                count_field(&count),
+                Field::Text(provenance(&count).to_string()),
+            ]
+        });
+        print_records(
+            format,
+            &["order", "sigma", "length", "count", "provenance"],
+            records,
+        );
+    }
+}
+
+// Print the simple cycles satisfying some restriction, grouped by length, in
+// any format
+fn print_restricted_cycles(
+    order: usize,
+    sigma: u8,
+    restriction: &str,
+    cycles: impl Iterator<Item = Cycle>,
+    format: Format,
+) {
+    if format == Format::Text {
+        let mut current_len = 0;
+        println!(
+            "In the de Bruijn graph dBG({}, {}), {}...",
+            order, sigma, restriction
+        );
+        for cycle in cycles {
+            if current_len != cycle_length(&cycle) {
+                current_len = cycle_length(&cycle);
+                println!("\n..the simple cycles of length {}", current_len);
             }
-            println!();
+            print_cycle(&cycle);
         }
+    } else {
+        let records = cycles.map(|cycle| cycle_record(order, sigma, cycle));
+        print_records(format, CYCLE_FIELDS, records);
     }
 }
+
+// Count the orbits of simple cycles up to some symmetries, in any format
+fn cli_count_up_to(length: usize, order: usize, sigma: u8, symmetry: Symmetry, format: Format) {
+    let group = symmetry.group(sigma);
+    let counts =
+        lengths(length, order, sigma).map(|l| (l, count_cycle_orbits(l, order, sigma, &group)));
+    let restriction = format!("up to {}", symmetry.describe());
+    print_restricted_counts(
+        order,
+        sigma,
+        &restriction,
+        "orbits of simple cycles",
+        counts,
+        format,
+    );
+}
+
+// Enumerate one simple cycle per orbit of isomorphic cycles, along with the
+// size of the orbit, in any format
+fn cli_enum_up_to(length: usize, order: usize, sigma: u8, symmetry: Symmetry, format: Format) {
+    let group = symmetry.group(sigma);
+    let orbits =
+        lengths(length, order, sigma).flat_map(|l| enum_cycle_orbits(l, order, sigma, &group));
+
+    if format == Format::Text {
+        let mut current_len = 0;
+        println!(
+            "In the de Bruijn graph dBG({}, {}), up to {}...",
+            order,
+            sigma,
+            symmetry.describe()
+        );
+        for (cycle, size) in orbits {
+            if current_len != cycle_length(&cycle) {
+                current_len = cycle_length(&cycle);
+                println!("\n..the simple cycles of length {}", current_len);
+            }
+            print_cycle(&cycle);
+            println!("    (orbit of {} cycles)", size);
+        }
+    } else {
+        let records = orbits.map(|(cycle, size)| {
+            let mut record = cycle_record(order, sigma, cycle);
+            record.push(Field::Number(size.to_string()));
+            record
+        });
+        print_records(format, &[CYCLE_FIELDS, &["orbit_size"]].concat(), records);
+    }
+}
+
+// Parse the order of the de Bruijn graph, that should be positive
+fn parse_order(s: &str) -> Result<usize, String> {
+    match s.parse::<usize>() {
+        Ok(0) => Err("The order should be positive".to_string()),
+        Ok(order) => Ok(order),
+        Err(e) => Err(format!("Invalid order {:?}: {}", s, e)),
+    }
+}
+
+// Parse a vertex given with dot-separated letters (as printed), e.g. 0.1.1
+fn parse_word(s: &str) -> Result<Word, String> {
+    s.split('.')
+        .map(|a| {
+            a.parse::<u8>()
+                .map_err(|e| format!("Invalid letter {:?}: {}", a, e))
+        })
+        .collect()
+}
+
+// Exit with an error message if the vertex is not a vertex of dBG(order, sigma)
+fn check_vertex(vertex: &Word, order: usize, sigma: u8) {
+    if vertex.len() != order || vertex.iter().any(|&a| a >= sigma) {
+        eprintln!(
+            "{} {} is not a vertex of dBG({}, {})",
+            "error:".red(),
+            word_label(vertex),
+            order,
+            sigma
+        );
+        std::process::exit(2);
+    }
+}
+
+// Count the simple cycles going through a given vertex, in any format
+fn cli_count_through(vertex: &Word, length: usize, order: usize, sigma: u8, format: Format) {
+    check_vertex(vertex, order, sigma);
+    let counts =
+        lengths(length, order, sigma).map(|l| (l, count_cycles_through(vertex, l, order, sigma)));
+    let restriction = format!("through the vertex {}", word_label(vertex));
+    print_restricted_counts(order, sigma, &restriction, "simple cycles", counts, format);
+}
+
+// Enumerate the simple cycles going through a given vertex, in any format
+fn cli_enum_through(vertex: &Word, length: usize, order: usize, sigma: u8, format: Format) {
+    check_vertex(vertex, order, sigma);
+    let cycles =
+        lengths(length, order, sigma).flat_map(|l| enum_cycles_through(vertex, l, order, sigma));
+    let restriction = format!("through the vertex {}", word_label(vertex));
+    print_restricted_cycles(order, sigma, &restriction, cycles, format);
+}
+
+// Read the forbidden vertices and edges from a file (see ForbiddenKmers::parse
+// for the format), exiting with an error message if this fails
+fn read_forbidden(path: &PathBuf, order: usize, sigma: u8) -> ForbiddenKmers {
+    let forbidden = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| ForbiddenKmers::parse(&text, order, sigma));
+    forbidden.unwrap_or_else(|e| {
+        eprintln!("{} {}: {}", "error:".red(), path.display(), e);
+        std::process::exit(2);
+    })
+}
+
+// Count the simple cycles avoiding some vertices and edges, in any format
+fn cli_count_avoiding(path: &PathBuf, length: usize, order: usize, sigma: u8, format: Format) {
+    let forbidden = read_forbidden(path, order, sigma);
+    let counts = lengths(length, order, sigma)
+        .map(|l| (l, count_cycles_avoiding(&forbidden, l, order, sigma)));
+    let restriction = format!("avoiding the k-mers of {}", path.display());
+    print_restricted_counts(order, sigma, &restriction, "simple cycles", counts, format);
+}
+
+// Enumerate the simple cycles avoiding some vertices and edges, in any format
+fn cli_enum_avoiding(path: &PathBuf, length: usize, order: usize, sigma: u8, format: Format) {
+    let forbidden = read_forbidden(path, order, sigma);
+    let cycles = lengths(length, order, sigma)
+        .flat_map(|l| enum_cycles_avoiding(&forbidden, l, order, sigma));
+    let restriction = format!("avoiding the k-mers of {}", path.display());
+    print_restricted_cycles(order, sigma, &restriction, cycles, format);
+}
+
+// Count the simple cycles found with Johnson's algorithm, in any format
+fn cli_count_johnson(length: usize, order: usize, sigma: u8, format: Format) {
+    let cycles = johnson_cycles(order, sigma);
+    let counts = lengths(length, order, sigma).map(|l| {
+        let nb_cycles = cycles.iter().filter(|c| c.len() == l + 1).count();
+        (l, Count::FromEnum(BigUint::from(nb_cycles)))
+    });
+    let restriction = "with Johnson's algorithm";
+    print_restricted_counts(order, sigma, restriction, "simple cycles", counts, format);
+}
+
+// Enumerate the simple cycles with Johnson's algorithm, in any format
+fn cli_enum_johnson(length: usize, order: usize, sigma: u8, format: Format) {
+    let cycles = johnson_cycles(order, sigma)
+        .into_iter()
+        .filter(|c| length == 0 || c.len() == length + 1);
+    let restriction = "with Johnson's algorithm";
+    print_restricted_cycles(order, sigma, restriction, cycles, format);
+}
+
+// Print the DOT description of the dbg. When a length is given, the simple
+// cycles of that length (or only some of them, given their indices) are
+// highlighted.
+fn cli_export(length: usize, order: usize, sigma: u8, indices: &[usize]) {
+    let mut cycles = Vec::new();
+    if length != 0 {
+        cycles = enum_cycles_fixed_length(length, order, sigma);
+        if !indices.is_empty() {
+            cycles = cycles
+                .into_iter()
+                .enumerate()
+                .filter(|(i, _)| indices.contains(i))
+                .map(|(_, cycle)| cycle)
+                .collect();
+        }
+    }
+    print!("{}", dbg_to_dot(order, sigma, &cycles));
+}
+
+// Print cycles of the dbg drawn uniformly at random, along with the seed
+// that allows to reproduce them.
+fn cli_sample(
+    length: usize,
+    order: usize,
+    sigma: u8,
+    number: usize,
+    seed: Option<u64>,
+    max_trials: u64,
+    format: Format,
+) {
+    let seed = seed.unwrap_or_else(|| {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+        now.as_nanos() as u64
+    });
+    let mut sampler = CycleSampler::new(length, order, sigma, seed, max_trials);
+    let cycles = (0..number)
+        .map(|_| sampler.sample_cycle())
+        .collect::<Result<Vec<_>, _>>()
+        .unwrap_or_else(|e| {
+            eprintln!("{} {}", "error:".red(), e);
+            std::process::exit(2);
+        });
+
+    if format == Format::Text {
+        println!(
+            "{} simple cycles of length {} drawn uniformly in dBG({}, {}) (seed {})",
+            cycles.len(),
+            length,
+            order,
+            sigma,
+            seed
+        );
+        for cycle in cycles {
+            print_cycle(&cycle);
+        }
+    } else {
+        let records = cycles
+            .into_iter()
+            .map(|cycle| cycle_record(order, sigma, cycle));
+        print_records(format, CYCLE_FIELDS, records);
+    }
+}
+
+// Generate a de Bruijn sequence with the given construction, in any format
+fn cli_dbs(order: usize, sigma: u8, construction: DbsConstruction, format: Format) {
+    let sequence =
+        de_bruijn_sequence(order, sigma, construction.construction()).unwrap_or_else(|e| {
+            eprintln!("{} {}", "error:".red(), e);
+            std::process::exit(2);
+        });
+    let name = construction
+        .to_possible_value()
+        .unwrap()
+        .get_name()
+        .to_string();
+
+    if format == Format::Text {
+        println!(
+            "A de Bruijn sequence of order {} over the [0..{}) alphabet ({})",
+            order, sigma, name
+        );
+        println!("  {}", word_label(&sequence));
+    } else {
+        let record = vec![
+            Field::Number(order.to_string()),
+            Field::Number(sigma.to_string()),
+            Field::Text(name),
+            Field::Word(sequence),
+        ];
+        print_records(
+            format,
+            &["order", "sigma", "construction", "sequence"],
+            std::iter::once(record),
+        );
+    }
+}
+
+// List the cycles of the state graph of a feedback shift register, whose truth
+// table is read from a file, in any format
+fn cli_feedback(path: &PathBuf, order: usize, format: Format) {
+    let f = std::fs::read_to_string(path)
+        .map_err(|e| e.to_string())
+        .and_then(|text| FeedbackFunction::parse(&text, order))
+        .unwrap_or_else(|e| {
+            eprintln!("{} {}: {}", "error:".red(), path.display(), e);
+            std::process::exit(2);
+        });
+    let cycles = f.cycles();
+
+    if format == Format::Text {
+        let singularity = match f.is_nonsingular() {
+            true => "nonsingular".green(),
+            false => "singular".yellow(),
+        };
+        println!(
+            "The state graph of the {} register of {} is made of {} simple cycles of dBG({}, 2)",
+            singularity,
+            path.display(),
+            cycles.len(),
+            order
+        );
+        for cycle in cycles.iter() {
+            print_cycle(cycle);
+        }
+    } else {
+        let records = cycles
+            .into_iter()
+            .map(|cycle| cycle_record(order, 2, cycle));
+        print_records(format, CYCLE_FIELDS, records);
+    }
+}
+
+//
+// Machine-readable outputs. Each record is a list of named fields, that are
+// rendered depending on the output format. Words are rendered as arrays in
+// JSON, and with dot-separated letters (as in the prose output) in CSV.
+//
+
+enum Field {
+    // A missing value, e.g. a count for which there is no formula
+    Null,
+    Number(String),
+    Text(String),
+    Word(Word),
+    Words(Vec<Word>),
+}
+
+// Text is escaped as a JSON string: quotes, backslashes and control characters
+// are prefixed by a backslash (or given by their code point)
+fn json_string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+// Fields are quoted in CSV whenever they contain a separator, a quote or a line
+// break, quotes being doubled (as in RFC 4180)
+fn csv_field(text: String) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text
+    }
+}
+
+fn field_to_json(field: &Field) -> String {
+    fn word_to_json(word: &Word) -> String {
+        let letters = word.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        format!("[{}]", letters.join(","))
+    }
+    match field {
+        Field::Null => "null".to_string(),
+        Field::Number(x) => x.clone(),
+        Field::Text(x) => json_string(x),
+        Field::Word(w) => word_to_json(w),
+        Field::Words(ws) => format!(
+            "[{}]",
+            ws.iter().map(word_to_json).collect::<Vec<_>>().join(",")
+        ),
+    }
+}
+
+fn field_to_csv(field: &Field) -> String {
+    fn word_to_csv(word: &Word) -> String {
+        let letters = word.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        letters.join(".")
+    }
+    csv_field(match field {
+        Field::Null => String::new(),
+        Field::Number(x) | Field::Text(x) => x.clone(),
+        Field::Word(w) => word_to_csv(w),
+        Field::Words(ws) => ws.iter().map(word_to_csv).collect::<Vec<_>>().join(" "),
+    })
+}
+
+// Records are printed as soon as they are produced
+fn print_records(format: Format, names: &[&str], records: impl Iterator<Item = Vec<Field>>) {
+    let to_json = |record: &Vec<Field>| {
+        let fields = names
+            .iter()
+            .zip(record)
+            .map(|(name, field)| format!("{}:{}", json_string(name), field_to_json(field)))
+            .collect::<Vec<_>>();
+        format!("{{{}}}", fields.join(","))
+    };
+
+    match format {
+        Format::Json => {
+            print!("[");
+            for (i, record) in records.enumerate() {
+                print!("{}\n  {}", if i == 0 { "" } else { "," }, to_json(&record));
+            }
+            println!("\n]");
+        }
+        Format::Ndjson => {
+            for record in records {
+                println!("{}", to_json(&record));
+            }
+        }
+        Format::Csv => {
+            println!("{}", names.join(","));
+            for record in records {
+                let fields = record.iter().map(field_to_csv).collect::<Vec<_>>();
+                println!("{}", fields.join(","));
+            }
+        }
+        Format::Text => unreachable!(),
+    }
+}
+
+// The provenance of a count, as displayed in the prose output
+fn provenance(count: &Count) -> &'static str {
+    match count {
+        Count::FromProvedFormula(_) => "proved",
+        Count::FromConjecturedFormula(_) => "conjectured",
+        Count::FromEnum(_) => "computed",
+        Count::NoFormula => "none",
+    }
+}
+
+// The field of a count, that is null when no formula applies
+fn count_field(count: &Count) -> Field {
+    match count.to_option() {
+        Some(x) => Field::Number(x.to_string()),
+        None => Field::Null,
+    }
+}
+
+// Machine-readable counterpart of cli_count, with one record per length
+fn cli_count_records(length: usize, order: usize, sigma: u8, format: Format) {
+    let records = lengths(length, order, sigma).map(|l| {
+        let count = count_cycles_with_formula(l, order, sigma, false);
+        vec![
+            Field::Number(order.to_string()),
+            Field::Number(sigma.to_string()),
+            Field::Number(l.to_string()),
+            count_field(&count),
+            Field::Text(provenance(&count).to_string()),
+        ]
+    });
+    print_records(
+        format,
+        &["order", "sigma", "length", "count", "provenance"],
+        records,
+    );
+}
+
+// Machine-readable counterpart of cli_enum, with one record per cycle (in the
+// same order)
+fn cli_enum_records(length: usize, order: usize, sigma: u8, format: Format) {
+    let cycles = if length != 0 {
+        enum_cycles_fixed_length(length, order, sigma)
+    } else {
+        let max_cycle_length = usize::pow(sigma as usize, order as u32);
+        let mut cycles = enum_cycles_bounded_length(max_cycle_length, order, sigma);
+        cycles.sort_by_key(|x| (x.len(), x.clone()));
+        cycles
+    };
+    let records = cycles
+        .into_iter()
+        .map(|cycle| cycle_record(order, sigma, cycle));
+    print_records(format, CYCLE_FIELDS, records);
+}
+
+// A cycle is described by its Lyndon word and its sequence of k-mers
+const CYCLE_FIELDS: &[&str] = &["order", "sigma", "length", "lyndon_word", "kmers"];
+
+// A cycle that cannot be mapped to a word (which the enumerations never
+// output) gets a null Lyndon word
+fn cycle_record(order: usize, sigma: u8, cycle: Cycle) -> Vec<Field> {
+    let lyndon_word = match map_cycle_to_word(&cycle) {
+        Ok(word) => Field::Word(word),
+        Err(_) => Field::Null,
+    };
+    vec![
+        Field::Number(order.to_string()),
+        Field::Number(sigma.to_string()),
+        Field::Number(cycle_length(&cycle).to_string()),
+        lyndon_word,
+        Field::Words(cycle),
+    ]
+}
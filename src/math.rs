@@ -6,15 +6,14 @@
 * were litteraly 30s to recode)
 *
 **/
+use num::BigUint;
 use reikna::totient::totient as phi;
 use slow_primes::Primes; // not that slow in fact :)
 
-// A naive factorial
-pub fn factorial(n: u32) -> u32 {
-    match n {
-        0 => 1,
-        _ => n * factorial(n - 1),
-    }
+// A naive factorial, over arbitrary-precision integers so that it can feed the
+// (quickly exploding) counting formulas
+pub fn factorial(n: u32) -> BigUint {
+    (1..=n).map(BigUint::from).product()
 }
 
 // The mobius function evaluates to: (A) 1 when call on 1 (B) 0 when call on n
@@ -28,13 +27,11 @@ pub fn mobius(n: usize) -> i8 {
     let sieve = Primes::sieve(10000);
     let decomposition = sieve.factor(n).unwrap();
     if decomposition.iter().any(|&(_, pow)| pow > 1) {
-        return 0;
+        0
+    } else if decomposition.len().is_multiple_of(2) {
+        1
     } else {
-        if decomposition.len() % 2 == 0 {
-            1
-        } else {
-            -1
-        }
+        -1
     }
 }
 
@@ -65,7 +62,17 @@ fn test_mobius() {
         0, 1, -1, -1, 0, -1, 1, -1, 0, 0, 1, -1, 0, -1, 1, 1, 0, -1, 0, -1, 0, 1, 1, -1, 0, 0, 1,
         0, 0, -1, -1, -1, 0, 1,
     ];
-    for i in 1..a008683.len() {
-        assert_eq!(mobius(i), a008683[i]);
+    for (i, &expected) in a008683.iter().enumerate().skip(1) {
+        assert_eq!(mobius(i), expected);
     }
 }
+
+#[test]
+fn test_factorial() {
+    assert_eq!(factorial(0), BigUint::from(1u32));
+    assert_eq!(factorial(5), BigUint::from(120u32));
+    assert_eq!(
+        factorial(25),
+        "15511210043330985984000000".parse::<BigUint>().unwrap()
+    );
+}
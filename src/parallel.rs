@@ -0,0 +1,137 @@
+/**
+*
+* #### parallel.rs ####
+*
+* Multi-threaded counterparts of the enumeration-based functions of count.rs
+* and enum.rs, available with the "parallel" feature.
+*
+* The generation tree of perfect Lyndon words of a fixed length (see
+* PerfectLyndonWordIter) is split into disjoint subtrees, one per prefix of a
+* given length. Each subtree is walked independently, with the same pruning as
+* the sequential generator (each thread reusing its own generator from one
+* prefix to the next). Prefixes being processed in lexicographic order, and
+* results being merged in that order, the output does not depend on the
+* scheduling of the threads.
+*
+**/
+use crate::count::Count;
+use crate::r#enum::map_word_to_cycle;
+use crate::perfect::PerfectLyndonWordIter;
+use crate::words::{Cycle, Word, all_words};
+use num::BigUint;
+use rayon::prelude::*;
+
+// The number of prefixes is chosen to be large enough for the work to be
+// balanced among threads, while being independent of their number.
+const MIN_NB_PREFIXES: usize = 1024;
+
+// The prefixes along which Lyndon words of the given length are split, that is
+// all the words of length prefix_len, in lexicographic order
+fn prefixes(length: usize, sigma: u8) -> Vec<Word> {
+    let mut prefix_len = 0;
+    while prefix_len < length && usize::pow(sigma as usize, prefix_len as u32) < MIN_NB_PREFIXES {
+        prefix_len += 1;
+    }
+    all_words(prefix_len, sigma)
+}
+
+// Map each subtree of the generation tree of perfect Lyndon words of fixed
+// length (e.g. to its number of words), in parallel
+fn par_map_subtrees<T: Send>(
+    length: usize,
+    order: usize,
+    sigma: u8,
+    f: impl Fn(&mut PerfectLyndonWordIter) -> T + Sync + Send,
+) -> impl ParallelIterator<Item = T> {
+    prefixes(length, sigma).into_par_iter().map_init(
+        move || PerfectLyndonWordIter::new(length, order, sigma),
+        move |iter, prefix| {
+            iter.set_prefix(&prefix);
+            f(iter)
+        },
+    )
+}
+
+pub fn par_count_cycles_only_enum(length: usize, order: usize, sigma: u8) -> Count {
+    let nb_plw = par_map_subtrees(length, order, sigma, |iter| iter.count()).sum::<usize>();
+    Count::FromEnum(BigUint::from(nb_plw))
+}
+
+// Same output as enum_cycles_fixed_length
+pub fn par_enum_cycles_fixed_length(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
+    par_map_subtrees(length, order, sigma, |iter| {
+        iter.map(|plw| map_word_to_cycle(plw, order))
+            .collect::<Vec<_>>()
+    })
+    .flatten_iter()
+    .collect()
+}
+
+// Same output as enum_cycles_bounded_length. Lengths are processed one after
+// the other, and perfect Lyndon words are sorted back into lexicographic order
+// before being mapped to cycles.
+pub fn par_enum_cycles_bounded_length(length: usize, order: usize, sigma: u8) -> Vec<Cycle> {
+    let mut plws = Vec::new();
+    for l in 1..=length {
+        plws.extend(
+            par_map_subtrees(l, order, sigma, |iter| iter.collect::<Vec<_>>())
+                .flatten_iter()
+                .collect::<Vec<_>>(),
+        );
+    }
+    plws.par_sort_unstable();
+    plws.into_par_iter()
+        .map(|plw| map_word_to_cycle(plw, order))
+        .collect()
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_par_count_cycles_only_enum() {
+    use crate::count::count_cycles_only_enum;
+
+    for (order, sigma) in [(3, 2), (2, 3), (4, 2)] {
+        for length in 1..=usize::pow(sigma as usize, order as u32) {
+            assert_eq!(
+                par_count_cycles_only_enum(length, order, sigma),
+                count_cycles_only_enum(length, order, sigma)
+            );
+        }
+    }
+    assert_eq!(
+        par_count_cycles_only_enum(14, 4, 2),
+        count_cycles_only_enum(14, 4, 2)
+    );
+}
+
+#[test]
+fn test_par_enum_cycles() {
+    use crate::r#enum::{enum_cycles_bounded_length, enum_cycles_fixed_length};
+
+    for length in 1..=9 {
+        assert_eq!(
+            par_enum_cycles_fixed_length(length, 3, 2),
+            enum_cycles_fixed_length(length, 3, 2)
+        );
+    }
+    assert_eq!(
+        par_enum_cycles_fixed_length(7, 2, 3),
+        enum_cycles_fixed_length(7, 2, 3)
+    );
+
+    assert_eq!(
+        par_enum_cycles_bounded_length(16, 4, 2),
+        enum_cycles_bounded_length(16, 4, 2)
+    );
+}
@@ -7,8 +7,17 @@
 * populate sorted-then-deduplicated list (akin of a set). The word is perfect if
 * the set is made of |w| elements.
 *
+* As this check is the bottleneck of the enumeration, an allocation-free
+* counterpart is further proposed, where k-mers are encoded as integers. The
+* same encoding is used to directly generate perfect Lyndon words, pruning the
+* generation tree of Lyndon words as soon as a k-mer is repeated. Some k-mers
+* (vertices) and (k+1)-mers (edges) can further be forbidden, which prunes the
+* generation tree as well.
+*
 **/
-use crate::words::Word;
+use crate::lyndon::{conjugacy_class_representative, is_lyndon};
+use crate::words::{Letter, Word};
+use std::collections::HashSet;
 
 pub fn is_perfect(word: &Word, k: usize) -> bool {
     let mut wword = word.clone();
@@ -47,11 +56,810 @@ pub fn is_perfect_lyndon(word: &Word, k: usize) -> bool {
     kmers.len() == word.len()
 }
 
+// When a word is not perfect, the way it fails to be so can be reported. The
+// witness describes the first k-mer (reading the word from left to right) that
+// has already been seen, all the positions at which it occurs in the cyclic
+// word, and the number of distinct k-mers of the word.
+#[derive(Debug, PartialEq)]
+pub struct PerfectnessWitness {
+    pub kmer: Word,
+    pub positions: Vec<usize>,
+    pub nb_distinct_kmers: usize,
+}
+
+// Returns None if the word is perfect, and a witness of the first repeated
+// k-mer otherwise.
+pub fn perfectness_witness(word: &Word, k: usize) -> Option<PerfectnessWitness> {
+    let n = word.len();
+    let kmers = (0..n)
+        .map(|i| (0..k).map(|j| word[(i + j) % n]).collect::<Word>())
+        .collect::<Vec<Word>>();
+
+    let mut seen = HashSet::new();
+    let kmer = kmers.iter().find(|&kmer| !seen.insert(kmer))?.clone();
+    let positions = (0..n).filter(|&i| kmers[i] == kmer).collect();
+    let nb_distinct_kmers = kmers.iter().collect::<HashSet<_>>().len();
+
+    Some(PerfectnessWitness {
+        kmer,
+        positions,
+        nb_distinct_kmers,
+    })
+}
+
+// The enumeration hot loops rather rely on a reusable checker. K-mers of the
+// (cyclic) word are encoded as integers in base sigma, computed in a rolling
+// fashion, and repetitions are detected using a scratch structure that is kept
+// across calls. Depending on the number sigma^k of possible k-mers, this
+// structure is (A) a bitset (B) a hash set of codes, when the bitset would be
+// too large (C) a list of positions to be sorted, when codes do not even fit in
+// a u64. The scratch structure is cleaned after each call, in time linear in
+// the length of the word.
+//
+// NOTE. Letters are assumed to be smaller than sigma.
+
+// Largest number of k-mers for which a bitset is used (i.e. 16MB)
+const MAX_BITSET_SIZE: u64 = 1 << 27;
+
+// The incremental structure behind (A) and (B), in which k-mer codes are
+// inserted and removed one at a time
+enum KmerSet {
+    Bitset(Vec<u64>),
+    Hashset(HashSet<u64>),
+}
+
+impl KmerSet {
+    // Returns None when codes do not fit in a u64, i.e. in case (C)
+    fn new(k: usize, sigma: u8) -> Option<Self> {
+        let nb = u64::checked_pow(sigma as u64, k as u32)?;
+        Some(match nb <= MAX_BITSET_SIZE {
+            true => KmerSet::Bitset(vec![0; nb.div_ceil(64) as usize]),
+            false => KmerSet::Hashset(HashSet::new()),
+        })
+    }
+
+    // Insert a k-mer code, returning false if it was already present
+    fn insert(&mut self, code: u64) -> bool {
+        match self {
+            KmerSet::Bitset(bits) => {
+                let (block, mask) = ((code / 64) as usize, 1 << (code % 64));
+                let is_new = bits[block] & mask == 0;
+                bits[block] |= mask;
+                is_new
+            }
+            KmerSet::Hashset(codes) => codes.insert(code),
+        }
+    }
+
+    fn remove(&mut self, code: u64) {
+        match self {
+            KmerSet::Bitset(bits) => bits[(code / 64) as usize] &= !(1 << (code % 64)),
+            KmerSet::Hashset(codes) => {
+                codes.remove(&code);
+            }
+        }
+    }
+}
+
+// The scratch structure of the checker, depending on the regime
+enum Scratch {
+    Codes(KmerSet),
+    Positions(Vec<usize>),
+}
+
+pub struct PerfectnessChecker {
+    k: usize,
+    sigma: u64,
+    // sigma^(k-1), the weight of the first letter of a k-mer
+    top: u64,
+    scratch: Scratch,
+}
+
+impl PerfectnessChecker {
+    pub fn new(k: usize, sigma: u8) -> Self {
+        assert!(k > 0, "The order of the de Bruijn graph should be positive");
+        PerfectnessChecker {
+            k,
+            sigma: sigma as u64,
+            top: u64::saturating_pow(sigma as u64, k as u32 - 1),
+            scratch: match KmerSet::new(k, sigma) {
+                Some(kmers) => Scratch::Codes(kmers),
+                None => Scratch::Positions(Vec::new()),
+            },
+        }
+    }
+
+    // Same semantic as is_perfect(word, k)
+    pub fn is_perfect(&mut self, word: &Word) -> bool {
+        let n = word.len();
+        let k = self.k;
+        if n == 0 {
+            return true;
+        }
+
+        let kmers = match &mut self.scratch {
+            Scratch::Codes(kmers) => kmers,
+            // The k-mers are too long to be encoded: positions of the word are
+            // sorted according to their k-mer, that are compared letter by
+            // letter
+            Scratch::Positions(positions) => {
+                let kmer = |i: usize| (0..k).map(move |j| word[(i + j) % n]);
+                positions.clear();
+                positions.extend(0..n);
+                positions.sort_unstable_by(|&a, &b| kmer(a).cmp(kmer(b)));
+                return positions.windows(2).all(|p| kmer(p[0]).ne(kmer(p[1])));
+            }
+        };
+
+        let (sigma, top) = (self.sigma, self.top);
+        let roll =
+            |code: u64, i: usize| (code - word[i] as u64 * top) * sigma + word[(i + k) % n] as u64;
+        let first_code = (0..k).fold(0, |code, j| code * sigma + word[j % n] as u64);
+
+        let mut nb_inserted = 0;
+        let mut code = first_code;
+        while nb_inserted < n && kmers.insert(code) {
+            code = roll(code, nb_inserted);
+            nb_inserted += 1;
+        }
+
+        // Clean the scratch structure for the next call
+        let mut code = first_code;
+        for i in 0..nb_inserted {
+            kmers.remove(code);
+            code = roll(code, i);
+        }
+
+        nb_inserted == n
+    }
+
+    // Same semantic as is_perfect_lyndon(word, k)
+    pub fn is_perfect_lyndon(&mut self, word: &Word) -> bool {
+        word.len() <= self.k || self.is_perfect(word)
+    }
+}
+
+// A set of forbidden vertices (k-mers) and edges ((k+1)-mers) of the de Bruijn
+// graph, whose removal yields the subgraph in which cycles are looked for.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ForbiddenKmers {
+    pub vertices: HashSet<Word>,
+    pub edges: HashSet<Word>,
+}
+
+impl ForbiddenKmers {
+    // Parse a list of forbidden vertices and edges, one per line, with
+    // dot-separated letters (e.g. 0.0.0 for a vertex of dBG(3, 2), and 0.1.1.0
+    // for an edge). Empty lines and lines starting with # are ignored.
+    pub fn parse(text: &str, k: usize, sigma: u8) -> Result<Self, String> {
+        let mut forbidden = ForbiddenKmers::default();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let word = line
+                .split('.')
+                .map(|a| a.parse::<Letter>().ok().filter(|&a| a < sigma))
+                .collect::<Option<Word>>()
+                .ok_or(format!(
+                    "Line {}: {:?} is not a word over [0..{})",
+                    i + 1,
+                    line,
+                    sigma
+                ))?;
+            if word.len() == k {
+                forbidden.vertices.insert(word);
+            } else if word.len() == k + 1 {
+                forbidden.edges.insert(word);
+            } else {
+                return Err(format!(
+                    "Line {}: {:?} is neither a vertex nor an edge of order {}",
+                    i + 1,
+                    line,
+                    k
+                ));
+            }
+        }
+        Ok(forbidden)
+    }
+
+    // Whether the last vertex or the last edge read along a (linear) word is
+    // forbidden
+    fn forbids_suffix(&self, word: &[Letter], k: usize) -> bool {
+        let n = word.len();
+        (n >= k && self.vertices.contains(&word[n - k..]))
+            || (n > k && self.edges.contains(&word[n - k - 1..]))
+    }
+
+    // Whether the cyclic word avoids all the forbidden vertices and edges
+    pub fn is_avoided_by(&self, word: &[Letter], k: usize) -> bool {
+        let n = word.len();
+        let window = |i: usize, len: usize| (0..len).map(|j| word[(i + j) % n]).collect::<Word>();
+        (0..n).all(|i| {
+            !self.vertices.contains(&window(i, k)) && !self.edges.contains(&window(i, k + 1))
+        })
+    }
+}
+
+// How the generators detect repeated k-mers: along the branches when k-mers
+// can be encoded, and only at the leaves otherwise
+enum Pruning {
+    Codes(KmerSet),
+    Leaves(PerfectnessChecker),
+}
+
+// The k-mer bookkeeping shared by the generators, that follows the (linear)
+// word under construction as letters are pushed and popped: the code of the
+// k-mer ending at each position, and the set of complete k-mers met so far.
+struct KmerStack {
+    k: usize,
+    sigma: u64,
+    // sigma^(k-1), so that the code of the k-1 last letters of a k-mer is
+    // obtained modulo top
+    top: u64,
+    codes: Vec<u64>,
+    pruning: Pruning,
+}
+
+impl KmerStack {
+    fn new(n: usize, k: usize, sigma: u8) -> Self {
+        KmerStack {
+            k,
+            sigma: sigma as u64,
+            top: u64::saturating_pow(sigma as u64, k.saturating_sub(1) as u32),
+            codes: Vec::with_capacity(n),
+            pruning: match KmerSet::new(k, sigma) {
+                Some(kmers) => Pruning::Codes(kmers),
+                None => Pruning::Leaves(PerfectnessChecker::new(k, sigma)),
+            },
+        }
+    }
+
+    // The length of the word
+    fn len(&self) -> usize {
+        self.codes.len()
+    }
+
+    // Append a letter to the word, unless it completes a k-mer that was already
+    // met. Returns whether the letter was appended.
+    fn push(&mut self, letter: Letter) -> bool {
+        let mut code = 0;
+        if let Pruning::Codes(kmers) = &mut self.pruning {
+            let previous_code = self.codes.last().copied().unwrap_or(0);
+            code = (previous_code % self.top) * self.sigma + letter as u64;
+            if self.codes.len() + 1 >= self.k && !kmers.insert(code) {
+                return false;
+            }
+        }
+        self.codes.push(code);
+        true
+    }
+
+    // Remove the last letter of the word
+    fn pop(&mut self) {
+        let code = self.codes.pop().unwrap();
+        if let Pruning::Codes(kmers) = &mut self.pruning
+            && self.codes.len() + 1 >= self.k
+        {
+            kmers.remove(code);
+        }
+    }
+
+    // Check the k-1 k-mers wrapping around the complete word, whose letters are
+    // given (the word being at least k-1 letters long)
+    fn wrapping_kmers_are_new(&mut self, word: &[Letter]) -> bool {
+        let kmers = match &mut self.pruning {
+            Pruning::Codes(kmers) => kmers,
+            Pruning::Leaves(checker) => return checker.is_perfect(&word.to_vec()),
+        };
+
+        let (sigma, top) = (self.sigma, self.top);
+        let last_code = *self.codes.last().unwrap();
+        let mut nb_inserted = 0;
+        let mut code = last_code;
+        while nb_inserted < self.k - 1 {
+            code = (code % top) * sigma + word[nb_inserted] as u64;
+            if !kmers.insert(code) {
+                break;
+            }
+            nb_inserted += 1;
+        }
+
+        let mut code = last_code;
+        for &letter in &word[..nb_inserted] {
+            code = (code % top) * sigma + letter as u64;
+            kmers.remove(code);
+        }
+
+        nb_inserted == self.k - 1
+    }
+}
+
+// Rather than generating all the Lyndon words of length n to filter them
+// afterwards, perfect Lyndon words can be generated directly. Prenecklaces are
+// built letter by letter, following the recursive FKM algorithm (the same tree
+// as the one walked by Duval's algorithm), and a branch is pruned as soon as
+// the linear k-mers of its prefix contain a repetition. At the leaves, the
+// remaining k-1 k-mers (that wrap around the word) are checked. Words are thus
+// yielded in lexicographic order.
+//
+// The recursion is unrolled into an explicit stack so that words are generated
+// lazily.
+pub struct PerfectLyndonWordIter {
+    n: usize,
+    k: usize,
+    sigma: u8,
+    // The prenecklace under construction, stored from index 1 (index 0 holds a
+    // sentinel 0, as in the source)
+    word: Word,
+    // For each position of the prenecklace, the length of the longest Lyndon
+    // prefix up to it
+    lyn: Vec<usize>,
+    kmers: KmerStack,
+    forbidden: Option<ForbiddenKmers>,
+    // The number of letters of the prefix that is left untouched
+    prefix_len: usize,
+    descending: bool,
+}
+
+impl PerfectLyndonWordIter {
+    // No word is generated when the length or the order is null
+    pub fn new(n: usize, k: usize, sigma: u8) -> Self {
+        PerfectLyndonWordIter {
+            n,
+            k,
+            sigma,
+            word: vec![0; n + 1],
+            lyn: Vec::with_capacity(n),
+            kmers: KmerStack::new(n, k, sigma),
+            forbidden: None,
+            prefix_len: 0,
+            descending: n > 0 && k > 0,
+        }
+    }
+
+    // The perfect Lyndon words of the subgraph where some vertices and edges
+    // are removed, i.e. whose cyclic k-mers and (k+1)-mers are not forbidden
+    pub fn avoiding(n: usize, k: usize, sigma: u8, forbidden: ForbiddenKmers) -> Self {
+        let mut iter = PerfectLyndonWordIter::new(n, k, sigma);
+        iter.forbidden = Some(forbidden);
+        iter
+    }
+
+    // Restrict the iteration to the words starting with the given prefix,
+    // restarting it from the smallest of them. This allows to split the
+    // generation tree into independent subtrees, while reusing the k-mer
+    // bookkeeping from one subtree to the next.
+    pub fn set_prefix(&mut self, prefix: &Word) {
+        while !self.lyn.is_empty() {
+            self.pop();
+        }
+        if self.n == 0 || self.k == 0 {
+            self.descending = false;
+            return;
+        }
+
+        // The prefix may not be one of a prenecklace, or already contain a
+        // repeated k-mer, in which case nothing is left to generate
+        for &letter in prefix.iter().take(self.n) {
+            if !self.place_from(letter) {
+                break;
+            }
+            if self.word[self.lyn.len()] != letter {
+                self.pop();
+                break;
+            }
+        }
+        self.prefix_len = self.lyn.len();
+        self.descending = self.prefix_len == prefix.len();
+    }
+
+    // Place the smallest valid letter, no smaller than min_letter, at the next
+    // position of the prenecklace. Returns false if there is none.
+    fn place_from(&mut self, min_letter: Letter) -> bool {
+        let i = self.lyn.len() + 1;
+        let p = self.lyn.last().copied().unwrap_or(1);
+        let base = self.word[i - p];
+        for letter in min_letter.max(base)..self.sigma {
+            if let Some(forbidden) = &self.forbidden {
+                self.word[i] = letter;
+                if forbidden.forbids_suffix(&self.word[1..=i], self.k) {
+                    continue;
+                }
+            }
+            if !self.kmers.push(letter) {
+                continue;
+            }
+            self.word[i] = letter;
+            self.lyn.push(if letter == base { p } else { i });
+            return true;
+        }
+        false
+    }
+
+    // Remove the last letter of the prenecklace, and return it
+    fn pop(&mut self) -> Letter {
+        let i = self.lyn.len();
+        self.lyn.pop();
+        self.kmers.pop();
+        self.word[i]
+    }
+
+    // Check the k-1 k-mers wrapping around the (complete) word
+    fn wrapping_kmers_are_new(&mut self) -> bool {
+        self.n <= self.k || self.kmers.wrapping_kmers_are_new(&self.word[1..])
+    }
+}
+
+impl Iterator for PerfectLyndonWordIter {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.descending {
+                if self.lyn.len() == self.n {
+                    // A leaf: the prenecklace is Lyndon iff it is its own
+                    // longest Lyndon prefix
+                    self.descending = false;
+                    if self.lyn[self.n - 1] == self.n
+                        && self.wrapping_kmers_are_new()
+                        && self
+                            .forbidden
+                            .as_ref()
+                            .is_none_or(|f| f.is_avoided_by(&self.word[1..], self.k))
+                    {
+                        return Some(self.word[1..].to_vec());
+                    }
+                } else if !self.place_from(0) {
+                    self.descending = false;
+                }
+            } else {
+                // Move to the next sibling of the deepest node, if any (the
+                // prefix being left untouched)
+                if self.lyn.len() == self.prefix_len {
+                    return None;
+                }
+                let letter = self.pop();
+                if self.place_from(letter + 1) {
+                    self.descending = true;
+                }
+            }
+        }
+    }
+}
+
+// Perfect words (not necessarily Lyndon) of length n, starting with a given
+// prefix, in lexicographic order. They are generated the same way, letter by
+// letter after the prefix, pruning a branch as soon as a linear k-mer is
+// repeated, and checking the k-1 wrapping k-mers at the leaves. The perfect
+// words starting with a k-mer v are the rotations, starting at v, of the cycles
+// going through the vertex v.
+//
+// NOTE. No word is generated when the order is null, or when the words are
+// shorter than the order (see rotations_through for these) or than the prefix.
+pub struct PerfectWordIter {
+    n: usize,
+    sigma: u8,
+    prefix_len: usize,
+    word: Word,
+    kmers: KmerStack,
+    descending: bool,
+    exhausted: bool,
+}
+
+impl PerfectWordIter {
+    pub fn with_prefix(prefix: &Word, n: usize, k: usize, sigma: u8) -> Self {
+        let mut iter = PerfectWordIter {
+            n,
+            sigma,
+            prefix_len: prefix.len(),
+            word: vec![0; n],
+            kmers: KmerStack::new(n, k, sigma),
+            descending: true,
+            exhausted: false,
+        };
+        if k == 0 || n < k || prefix.len() > n {
+            iter.exhausted = true;
+            return iter;
+        }
+
+        // The prefix itself may already contain a repeated k-mer
+        for &letter in prefix {
+            if !iter.place_from(letter) || iter.word[iter.kmers.len() - 1] != letter {
+                iter.exhausted = true;
+                break;
+            }
+        }
+        iter
+    }
+
+    // Place the smallest valid letter, no smaller than min_letter, at the next
+    // position of the word. Returns false if there is none.
+    fn place_from(&mut self, min_letter: Letter) -> bool {
+        let i = self.kmers.len();
+        for letter in min_letter..self.sigma {
+            if self.kmers.push(letter) {
+                self.word[i] = letter;
+                return true;
+            }
+        }
+        false
+    }
+
+    // Remove the last letter of the word, and return it
+    fn pop(&mut self) -> Letter {
+        self.kmers.pop();
+        self.word[self.kmers.len()]
+    }
+}
+
+impl Iterator for PerfectWordIter {
+    type Item = Word;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.exhausted {
+            return None;
+        }
+        loop {
+            if self.descending {
+                if self.kmers.len() == self.n {
+                    self.descending = false;
+                    if self.kmers.wrapping_kmers_are_new(&self.word) {
+                        return Some(self.word.clone());
+                    }
+                } else if !self.place_from(0) {
+                    self.descending = false;
+                }
+            } else {
+                // Move to the next sibling of the deepest node, if any (the
+                // prefix being left untouched)
+                if self.kmers.len() == self.prefix_len {
+                    self.exhausted = true;
+                    return None;
+                }
+                let letter = self.pop();
+                if self.place_from(letter + 1) {
+                    self.descending = true;
+                }
+            }
+        }
+    }
+}
+
+// The rotations, starting at the given vertex, of the perfect words of the
+// cycles of fixed length going through that vertex. When the length is no
+// smaller than the order, these are the perfect words starting with the vertex.
+// Otherwise, there is (at most) one such cycle, whose word is read along the
+// vertex, provided that the vertex is periodic enough. There is none if the
+// given word is not a vertex of the graph.
+pub(crate) fn rotations_through(
+    vertex: &Word,
+    length: usize,
+    order: usize,
+    sigma: u8,
+) -> impl Iterator<Item = Word> {
+    let is_vertex = vertex.len() == order && vertex.iter().all(|&a| a < sigma);
+    let (mut long, mut short) = (None, None);
+    if is_vertex && length >= order {
+        long = Some(PerfectWordIter::with_prefix(vertex, length, order, sigma));
+    } else if is_vertex && length > 0 {
+        let word = vertex[..length].to_vec();
+        let is_periodic = (length..order).all(|i| vertex[i] == vertex[i - length]);
+        if is_periodic && is_lyndon(&conjugacy_class_representative(&word)) {
+            short = Some(word);
+        }
+    }
+    long.into_iter().flatten().chain(short)
+}
+
 #[test]
 fn test_is_perfect() {
-    assert_eq!(is_perfect(&vec![0, 1, 0, 1, 1], 2), false);
-    assert_eq!(is_perfect(&vec![0, 1, 0, 1, 1], 3), false);
-    assert_eq!(is_perfect(&vec![0, 1, 0, 1, 1], 4), true);
-    assert_eq!(is_perfect(&vec![0, 1, 0, 1, 1], 5), true);
-    assert_eq!(is_perfect(&vec![0, 1, 0, 1, 1], 6), true);
+    assert!(!is_perfect(&vec![0, 1, 0, 1, 1], 2));
+    assert!(!is_perfect(&vec![0, 1, 0, 1, 1], 3));
+    assert!(is_perfect(&vec![0, 1, 0, 1, 1], 4));
+    assert!(is_perfect(&vec![0, 1, 0, 1, 1], 5));
+    assert!(is_perfect(&vec![0, 1, 0, 1, 1], 6));
+}
+
+#[test]
+fn test_perfectness_checker() {
+    // All words of length up to 8 over {0, 1, 2} (that are long enough for
+    // is_perfect to apply)
+    for k in 1..=4 {
+        let mut checker = PerfectnessChecker::new(k, 3);
+        for n in (k - 1).max(1)..=8 {
+            for code in 0..usize::pow(3, n as u32) {
+                let word = (0..n)
+                    .map(|i| ((code / usize::pow(3, i as u32)) % 3) as u8)
+                    .collect::<Word>();
+                assert_eq!(checker.is_perfect(&word), is_perfect(&word, k));
+                assert_eq!(
+                    checker.is_perfect_lyndon(&word),
+                    is_perfect_lyndon(&word, k)
+                );
+            }
+        }
+    }
+
+    // Hash set and sorted positions regimes, on a pseudo-random word and on a
+    // square (hence non-perfect) word
+    let mut state: u32 = 42;
+    let random_word = (0..80)
+        .map(|_| {
+            state = state.wrapping_mul(1664525).wrapping_add(1013904223);
+            (state >> 31) as u8
+        })
+        .collect::<Word>();
+    let square_word = [&random_word[..40], &random_word[..40]].concat();
+    for k in [40, 70] {
+        let mut checker = PerfectnessChecker::new(k, 2);
+        assert!(is_perfect(&random_word, k));
+        assert!(checker.is_perfect(&random_word));
+        assert!(!checker.is_perfect(&square_word));
+        assert!(checker.is_perfect(&random_word));
+    }
+}
+
+#[test]
+fn test_perfect_lyndon_word_iter() {
+    use crate::lyndon::LyndonWord;
+
+    for (k, sigma) in [(1, 2), (2, 2), (3, 2), (4, 2), (2, 3), (3, 3), (1, 4)] {
+        for n in 1..=usize::min(12, usize::pow(sigma as usize, k as u32) + 1) {
+            let expected = LyndonWord::new_smallest(n, sigma - 1)
+                .into_iter(true)
+                .filter(|w| is_perfect_lyndon(w, k))
+                .collect::<Vec<_>>();
+            let generated = PerfectLyndonWordIter::new(n, k, sigma).collect::<Vec<_>>();
+            assert_eq!(generated, expected);
+        }
+    }
+
+    // Cycles longer than the number of vertices do not exist
+    assert_eq!(PerfectLyndonWordIter::new(20, 3, 2).count(), 0);
+
+    // Null lengths and orders
+    assert_eq!(PerfectLyndonWordIter::new(0, 3, 2).next(), None);
+    assert_eq!(PerfectLyndonWordIter::new(1, 0, 2).next(), None);
+
+    // Splitting along prefixes
+    let mut iter = PerfectLyndonWordIter::new(11, 4, 2);
+    let mut generated = Vec::new();
+    for prefix in crate::words::all_words(3, 2) {
+        iter.set_prefix(&prefix);
+        generated.extend(iter.by_ref());
+    }
+    let expected = PerfectLyndonWordIter::new(11, 4, 2).collect::<Vec<_>>();
+    assert_eq!(generated, expected);
+    iter.set_prefix(&vec![1, 0]);
+    assert_eq!(iter.next(), None);
+    iter.set_prefix(&vec![0; 12]);
+    assert_eq!(iter.next(), None);
+}
+
+#[test]
+fn test_perfectness_witness() {
+    assert_eq!(
+        perfectness_witness(&vec![0, 1, 0, 1, 1], 2),
+        Some(PerfectnessWitness {
+            kmer: vec![0, 1],
+            positions: vec![0, 2],
+            nb_distinct_kmers: 3,
+        })
+    );
+    assert_eq!(
+        perfectness_witness(&vec![0, 1, 0, 1, 1], 3),
+        Some(PerfectnessWitness {
+            kmer: vec![1, 0, 1],
+            positions: vec![1, 4],
+            nb_distinct_kmers: 4,
+        })
+    );
+    assert_eq!(perfectness_witness(&vec![0, 1, 0, 1, 1], 4), None);
+    assert_eq!(
+        perfectness_witness(&vec![0, 0, 0, 0, 1, 0, 0], 2),
+        Some(PerfectnessWitness {
+            kmer: vec![0, 0],
+            positions: vec![0, 1, 2, 5, 6],
+            nb_distinct_kmers: 3,
+        })
+    );
+
+    // Consistency with is_perfect
+    for code in 0..u32::pow(2, 10) {
+        let word = (0..10).map(|i| ((code >> i) & 1) as u8).collect::<Word>();
+        for k in 1..=6 {
+            assert_eq!(
+                perfectness_witness(&word, k).is_none(),
+                is_perfect(&word, k)
+            );
+        }
+    }
+}
+
+#[test]
+fn test_perfect_word_iter() {
+    // Compare with the filtering of all words with the prefix
+    for (n, k, sigma) in [(8, 3, 2), (6, 3, 2), (3, 3, 2), (7, 2, 3), (4, 4, 2)] {
+        for prefix in [vec![], vec![0, 1], vec![1, 1, 0], vec![0, 0, 0, 0]] {
+            if prefix.len() > n || prefix.iter().any(|&a| a >= sigma) {
+                continue;
+            }
+            let expected = crate::words::all_words(n, sigma)
+                .into_iter()
+                .filter(|w| w.starts_with(&prefix) && is_perfect(w, k))
+                .collect::<Vec<_>>();
+            assert_eq!(
+                PerfectWordIter::with_prefix(&prefix, n, k, sigma).collect::<Vec<_>>(),
+                expected
+            );
+        }
+    }
+
+    // Unencodable k-mers: 0^70 11 is the only perfect extension of 0^70
+    let mut iter = PerfectWordIter::with_prefix(&vec![0; 70], 72, 70, 2);
+    assert_eq!(iter.next().unwrap()[70..], [1, 1]);
+    assert_eq!(iter.next(), None);
+
+    // Null orders, and words shorter than the order or than the prefix
+    assert_eq!(PerfectWordIter::with_prefix(&vec![0], 2, 0, 2).next(), None);
+    assert_eq!(PerfectWordIter::with_prefix(&vec![0], 2, 3, 2).next(), None);
+    assert_eq!(
+        PerfectWordIter::with_prefix(&vec![0; 5], 4, 3, 2).next(),
+        None
+    );
+
+    // Words that are not vertices
+    assert_eq!(rotations_through(&vec![0, 1], 4, 3, 2).next(), None);
+    assert_eq!(rotations_through(&vec![0, 1, 2], 4, 3, 2).next(), None);
+}
+
+#[test]
+fn test_forbidden_kmers() {
+    let forbidden = ForbiddenKmers::parse("# Homopolymers\n0.0.0\n1.1.1\n\n0.1.0.1\n", 3, 2);
+    let forbidden = forbidden.unwrap();
+    assert_eq!(forbidden.vertices.len(), 2);
+    assert_eq!(forbidden.edges.len(), 1);
+    assert!(ForbiddenKmers::parse("0.1", 3, 2).is_err());
+    assert!(ForbiddenKmers::parse("0.1.2", 3, 2).is_err());
+    assert!(ForbiddenKmers::parse("0.x.1", 3, 2).is_err());
+
+    // Compare with the filtering of all perfect Lyndon words
+    for (n, k, sigma, text) in [
+        (6, 3, 2, "0.0.0\n1.1.1"),
+        (7, 3, 2, "0.1.1.0"),
+        (2, 3, 2, "1.0.1"),
+        (9, 2, 3, "0.0\n1.2.0"),
+        (12, 4, 2, "0.1.0.1\n1.1.0.0.1"),
+    ] {
+        let expected = PerfectLyndonWordIter::new(n, k, sigma)
+            .filter(|w| {
+                ForbiddenKmers::parse(text, k, sigma)
+                    .unwrap()
+                    .is_avoided_by(w, k)
+            })
+            .collect::<Vec<_>>();
+        let forbidden = ForbiddenKmers::parse(text, k, sigma).unwrap();
+        let words = PerfectLyndonWordIter::avoiding(n, k, sigma, forbidden).collect::<Vec<_>>();
+        assert_eq!(words, expected);
+    }
+
+    // The 0.1 cycle goes through the vertices 0.1.0 and 1.0.1, and the edges
+    // 0.1.0.1 and 1.0.1.0
+    assert!(
+        !ForbiddenKmers::parse("1.0.1", 3, 2)
+            .unwrap()
+            .is_avoided_by(&[0, 1], 3)
+    );
+    assert!(
+        !ForbiddenKmers::parse("1.0.1.0", 3, 2)
+            .unwrap()
+            .is_avoided_by(&[0, 1], 3)
+    );
+    assert!(
+        ForbiddenKmers::parse("1.1.0", 3, 2)
+            .unwrap()
+            .is_avoided_by(&[0, 1], 3)
+    );
 }
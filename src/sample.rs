@@ -0,0 +1,175 @@
+/**
+*
+* #### sample.rs ####
+*
+* When cycles are too numerous to be enumerated, they can still be sampled
+* uniformly at random. Words of the requested length are drawn uniformly, and
+* rejected until a perfect one is met. Perfect words being primitive, each
+* perfect Lyndon word is reached through exactly as many words as its length
+* (its rotations), so that taking the Lyndon rotation of the accepted word
+* yields a uniform perfect Lyndon word, hence a uniform simple cycle.
+*
+* NOTE. The expected number of trials is the inverse of the proportion of
+* perfect words, that can be (very) large when the length of the cycles gets
+* close to the number of vertices. The number of trials per cycle is thus
+* bounded, and a draw fails when it is exceeded.
+*
+**/
+use crate::r#enum::map_word_to_cycle;
+use crate::lyndon::conjugacy_class_representative;
+use crate::perfect::PerfectnessChecker;
+use crate::words::{Cycle, Word};
+
+// A small seedable pseudo-random generator (SplitMix64), that is enough to
+// draw random words reproducibly without pulling a whole crate.
+struct SplitMix64 {
+    state: u64,
+}
+
+impl SplitMix64 {
+    fn new(seed: u64) -> Self {
+        SplitMix64 { state: seed }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9e3779b97f4a7c15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d049bb133111eb);
+        z ^ (z >> 31)
+    }
+
+    // A uniform integer in [0..bound), rejecting the values of the last
+    // (incomplete) block to avoid any bias
+    fn below(&mut self, bound: u64) -> u64 {
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let x = self.next_u64();
+            if x < zone {
+                return x % bound;
+            }
+        }
+    }
+}
+
+// A sampler of independent uniform simple cycles of a given length, that is
+// reproducible given its seed.
+pub struct CycleSampler {
+    length: usize,
+    order: usize,
+    sigma: u8,
+    // The number of words drawn for each cycle before giving up
+    max_trials: u64,
+    checker: PerfectnessChecker,
+    rng: SplitMix64,
+}
+
+impl CycleSampler {
+    pub fn new(length: usize, order: usize, sigma: u8, seed: u64, max_trials: u64) -> Self {
+        CycleSampler {
+            length,
+            order,
+            sigma,
+            max_trials,
+            checker: PerfectnessChecker::new(order, sigma),
+            rng: SplitMix64::new(seed),
+        }
+    }
+
+    // Draw words until a perfect one is met, and return its Lyndon rotation. An
+    // error is returned if there is no simple cycle of the length, or if no
+    // perfect word is met within the maximum number of trials.
+    pub fn sample_perfect_lyndon_word(&mut self) -> Result<Word, String> {
+        // The de Bruijn graph has simple cycles of every length up to its
+        // number of vertices (and of no larger length)
+        let nb_vertices = u32::checked_pow(self.sigma as u32, self.order as u32);
+        if self.length == 0 || nb_vertices.is_some_and(|nb| self.length > nb as usize) {
+            return Err(format!(
+                "There is no simple cycle of length {} in dBG({}, {})",
+                self.length, self.order, self.sigma
+            ));
+        }
+
+        let mut word = vec![0; self.length];
+        for _ in 0..self.max_trials {
+            for letter in word.iter_mut() {
+                *letter = self.rng.below(self.sigma as u64) as u8;
+            }
+            if self.checker.is_perfect(&word) {
+                return Ok(conjugacy_class_representative(&word));
+            }
+        }
+        Err(format!(
+            "No perfect word of length {} was drawn within {} trials",
+            self.length, self.max_trials
+        ))
+    }
+
+    // The simple cycle encoded by a sampled perfect Lyndon word
+    pub fn sample_cycle(&mut self) -> Result<Cycle, String> {
+        self.sample_perfect_lyndon_word()
+            .map(|plw| map_word_to_cycle(plw, self.order))
+    }
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_cycle_sampler() {
+    use crate::r#enum::enum_cycles_fixed_length;
+
+    // Reproducibility
+    let mut a = CycleSampler::new(7, 3, 2, 42, 1000);
+    let mut b = CycleSampler::new(7, 3, 2, 42, 1000);
+    for _ in 0..20 {
+        assert_eq!(a.sample_cycle(), b.sample_cycle());
+    }
+
+    // Uniformity, roughly: each of the 4 cycles of length 7 of dBG(3, 2) should
+    // be drawn about 1000 times
+    let cycles = enum_cycles_fixed_length(7, 3, 2);
+    let mut hits = vec![0; cycles.len()];
+    let mut sampler = CycleSampler::new(7, 3, 2, 0, 1000);
+    for _ in 0..4000 {
+        let cycle = sampler.sample_cycle().unwrap();
+        hits[cycles.iter().position(|c| *c == cycle).unwrap()] += 1;
+    }
+    assert!(hits.iter().all(|&h| (850..1150).contains(&h)));
+
+    // Lengths with no cycle
+    assert!(CycleSampler::new(9, 3, 2, 0, 1000).sample_cycle().is_err());
+    assert!(CycleSampler::new(0, 3, 2, 0, 1000).sample_cycle().is_err());
+
+    // Lengths for which perfect words are too rare: the 2^26 de Bruijn cycles
+    // of dBG(6, 2) are read by only 2^32 of the 2^64 binary words of length 64
+    assert!(
+        CycleSampler::new(64, 6, 2, 0, 1000)
+            .sample_perfect_lyndon_word()
+            .is_err()
+    );
+
+    // Lengths no larger than the order
+    let cycle = CycleSampler::new(2, 3, 2, 0, 1000).sample_cycle().unwrap();
+    assert_eq!(cycle, [[0, 1, 0], [1, 0, 1], [0, 1, 0]]);
+}
+
+#[test]
+fn test_splitmix64() {
+    // Reference values of the SplitMix64 generator seeded with 0
+    let mut rng = SplitMix64::new(0);
+    assert_eq!(rng.next_u64(), 0xe220a8397b1dcdaf);
+    assert_eq!(rng.next_u64(), 0x6e789e6aa1b965f4);
+
+    let mut rng = SplitMix64::new(42);
+    assert!((0..1000).all(|_| rng.below(7) < 7));
+}
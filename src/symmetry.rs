@@ -0,0 +1,268 @@
+/**
+*
+* #### symmetry.rs ####
+*
+* The automorphisms of the de Bruijn graph dBG(k, sigma) include the
+* permutations of the alphabet (applied letterwise to the vertices, e.g. the
+* complement a -> sigma - 1 - a), as well as the reversal of the vertices, that
+* maps the graph onto its reversed graph (hence maps cycles onto cycles,
+* traversed backwards). Cycles are thus partitioned into orbits of isomorphic
+* cycles, under the group generated by some of these symmetries.
+*
+* Cycles being encoded by perfect Lyndon words, these symmetries act on words,
+* up to rotation. The canonical representative of an orbit is the smallest
+* Lyndon word among the images of any of its words, and its size is the number
+* of distinct such images. The number of orbits can be cross-checked with the
+* lemma of Burnside, as the average number of cycles fixed by the elements of
+* the group.
+*
+**/
+use crate::count::Count;
+use crate::r#enum::map_word_to_cycle;
+use crate::lyndon::conjugacy_class_representative;
+use crate::perfect::PerfectLyndonWordIter;
+use crate::words::{Cycle, Letter, Word};
+use num::BigUint;
+
+// A symmetry of the graph: letters are renamed through the permutation, and
+// then the word is possibly reversed
+struct Transformation {
+    permutation: Vec<Letter>,
+    reversed: bool,
+}
+
+impl Transformation {
+    // The image of a word, up to rotation (i.e. its Lyndon rotation)
+    fn apply(&self, word: &Word) -> Word {
+        let mut image = word
+            .iter()
+            .map(|&a| self.permutation[a as usize])
+            .collect::<Word>();
+        if self.reversed {
+            image.reverse();
+        }
+        conjugacy_class_representative(&image)
+    }
+}
+
+// All the permutations of [0..sigma), in lexicographic order (the identity
+// coming first)
+pub(crate) fn permutations(sigma: u8) -> Vec<Vec<Letter>> {
+    let mut permutation = (0..sigma).collect::<Vec<_>>();
+    let mut permutations = vec![permutation.clone()];
+    loop {
+        // Narayana's next permutation algorithm
+        let Some(i) = (1..permutation.len())
+            .rev()
+            .find(|&i| permutation[i - 1] < permutation[i])
+        else {
+            return permutations;
+        };
+        let j = (i..permutation.len())
+            .rev()
+            .find(|&j| permutation[j] > permutation[i - 1])
+            .unwrap();
+        permutation.swap(i - 1, j);
+        permutation[i..].reverse();
+        permutations.push(permutation.clone());
+    }
+}
+
+// A group of symmetries of the de Bruijn graph over [0..sigma), generated by
+// the permutations of the alphabet (if permutation is set) and the reversal (if
+// reversal is set).
+pub struct SymmetryGroup {
+    transformations: Vec<Transformation>,
+}
+
+impl SymmetryGroup {
+    pub fn new(sigma: u8, permutation: bool, reversal: bool) -> Self {
+        let permutations = match permutation {
+            true => permutations(sigma),
+            false => vec![(0..sigma).collect()],
+        };
+        let mut transformations = Vec::new();
+        for reversed in [false, true] {
+            if reversed && !reversal {
+                continue;
+            }
+            for permutation in permutations.iter() {
+                transformations.push(Transformation {
+                    permutation: permutation.clone(),
+                    reversed,
+                });
+            }
+        }
+        SymmetryGroup { transformations }
+    }
+
+    pub fn order(&self) -> usize {
+        self.transformations.len()
+    }
+
+    // The canonical representative of the orbit of a word, up to rotation
+    pub fn canonical_form(&self, word: &Word) -> Word {
+        self.transformations
+            .iter()
+            .map(|g| g.apply(word))
+            .min()
+            .unwrap()
+    }
+
+    // Whether a Lyndon word is the canonical representative of its orbit. This
+    // avoids computing all the images, stopping at the first smaller one.
+    pub fn is_canonical(&self, lw: &Word) -> bool {
+        self.transformations.iter().all(|g| g.apply(lw) >= *lw)
+    }
+
+    // The number of distinct words (up to rotation) in the orbit of a word
+    pub fn orbit_size(&self, word: &Word) -> usize {
+        let mut orbit = self
+            .transformations
+            .iter()
+            .map(|g| g.apply(word))
+            .collect::<Vec<_>>();
+        orbit.sort();
+        orbit.dedup();
+        orbit.len()
+    }
+}
+
+// One representative cycle per orbit of cycles of fixed length, along with the
+// size of the orbit. Representatives come in the order of their Lyndon words.
+pub fn iter_cycle_orbits<'a>(
+    length: usize,
+    order: usize,
+    sigma: u8,
+    group: &'a SymmetryGroup,
+) -> impl Iterator<Item = (Cycle, usize)> + 'a {
+    PerfectLyndonWordIter::new(length, order, sigma)
+        .filter(|plw| group.is_canonical(plw))
+        .map(move |plw| {
+            let size = group.orbit_size(&plw);
+            (map_word_to_cycle(plw, order), size)
+        })
+}
+
+pub fn enum_cycle_orbits(
+    length: usize,
+    order: usize,
+    sigma: u8,
+    group: &SymmetryGroup,
+) -> Vec<(Cycle, usize)> {
+    iter_cycle_orbits(length, order, sigma, group).collect()
+}
+
+pub fn count_cycle_orbits(length: usize, order: usize, sigma: u8, group: &SymmetryGroup) -> Count {
+    let nb_orbits = PerfectLyndonWordIter::new(length, order, sigma)
+        .filter(|plw| group.is_canonical(plw))
+        .count();
+    Count::FromEnum(BigUint::from(nb_orbits))
+}
+
+// The number of orbits, following Burnside's lemma: this is the average, over
+// the elements of the group, of the number of cycles they fix.
+pub fn count_cycle_orbits_burnside(
+    length: usize,
+    order: usize,
+    sigma: u8,
+    group: &SymmetryGroup,
+) -> Count {
+    let mut nb_fixed_points = 0;
+    for plw in PerfectLyndonWordIter::new(length, order, sigma) {
+        nb_fixed_points += group
+            .transformations
+            .iter()
+            .filter(|g| g.apply(&plw) == plw)
+            .count();
+    }
+    // Burnside's lemma: the total number of fixed points is a multiple of the
+    // order of the group
+    assert_eq!(nb_fixed_points % group.order(), 0);
+    Count::FromEnum(BigUint::from(nb_fixed_points / group.order()))
+}
+
+//
+//
+//
+// ,--. ,--.        ,--.  ,--.        ,--.                 ,--.
+// |  | |  |,--,--, `--',-'  '-.    ,-'  '-. ,---.  ,---.,-'  '-. ,---.
+// |  | |  ||      \,--.'-.  .-'    '-.  .-'| .-. :(  .-''-.  .-'(  .-'
+// '  '-'  '|  ||  ||  |  |  |        |  |  \   --..-'  `) |  |  .-'  `)
+//  `-----' `--''--'`--'  `--'        `--'   `----'`----'  `--'  `----'
+//
+//
+//
+
+#[test]
+fn test_symmetry_group() {
+    assert_eq!(permutations(3).len(), 6);
+    assert!(permutations(4).is_sorted());
+    assert_eq!(SymmetryGroup::new(3, true, true).order(), 12);
+    assert_eq!(SymmetryGroup::new(3, false, true).order(), 2);
+    assert_eq!(SymmetryGroup::new(3, false, false).order(), 1);
+
+    // 0010111 is mapped to 0001101 by complement, to 0011101 by reversal, and
+    // to 0001011 by both
+    let word = vec![0, 0, 1, 0, 1, 1, 1];
+    let group = SymmetryGroup::new(2, true, true);
+    assert_eq!(group.canonical_form(&word), [0, 0, 0, 1, 0, 1, 1]);
+    assert_eq!(group.orbit_size(&word), 4);
+    assert!(!group.is_canonical(&word));
+    assert!(group.is_canonical(&vec![0, 0, 0, 1, 0, 1, 1]));
+
+    // 01 is stable under complement
+    assert_eq!(group.orbit_size(&vec![0, 1]), 1);
+}
+
+#[test]
+fn test_cycle_orbits() {
+    use crate::count::count_cycles_only_enum;
+    use crate::r#enum::{enum_cycles_up_to_reversal, map_cycle_to_word};
+
+    for (length, order, sigma) in [
+        (6, 3, 2),
+        (7, 3, 2),
+        (8, 3, 2),
+        (5, 2, 3),
+        (9, 2, 3),
+        (12, 4, 2),
+    ] {
+        for (permutation, reversal) in [(false, false), (true, false), (false, true), (true, true)]
+        {
+            let group = SymmetryGroup::new(sigma, permutation, reversal);
+            let orbits = enum_cycle_orbits(length, order, sigma, &group);
+
+            // Orbits partition the cycles
+            let nb_cycles = orbits.iter().map(|(_, size)| size).sum::<usize>();
+            assert_eq!(
+                Count::FromEnum(BigUint::from(nb_cycles)),
+                count_cycles_only_enum(length, order, sigma)
+            );
+
+            // Burnside's lemma
+            let count = count_cycle_orbits(length, order, sigma, &group);
+            assert_eq!(count, Count::FromEnum(BigUint::from(orbits.len())));
+            assert_eq!(
+                count,
+                count_cycle_orbits_burnside(length, order, sigma, &group)
+            );
+
+            // Reversal alone matches enum_cycles_up_to_reversal
+            if !permutation && reversal {
+                let cycles = orbits.into_iter().map(|(c, _)| c).collect::<Vec<_>>();
+                assert_eq!(cycles, enum_cycles_up_to_reversal(length, order, sigma));
+            }
+        }
+    }
+
+    // The two de Bruijn cycles of dBG(3, 2) are isomorphic (under reversal as
+    // well as under complement)
+    let orbits = enum_cycle_orbits(8, 3, 2, &SymmetryGroup::new(2, true, false));
+    assert_eq!(orbits.len(), 1);
+    assert_eq!(
+        map_cycle_to_word(&orbits[0].0).unwrap(),
+        [0, 0, 0, 1, 0, 1, 1, 1]
+    );
+    assert_eq!(orbits[0].1, 2);
+}
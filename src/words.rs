@@ -2,10 +2,35 @@
 *
 * #### words.rs ####
 *
-* Abstraction of data type for the mathematical object we work with.
+* Abstraction of data type for the mathematical object we work with, and a few
+* helpers to build them.
 *
 **/
-
 pub type Letter = u8;
 pub type Word = Vec<Letter>;
 pub type Cycle = Vec<Word>;
+
+// The length of a cycle, that is its number of edges. Its vertices are listed
+// from the first one back to itself, which thus appears twice.
+pub fn cycle_length(cycle: &Cycle) -> usize {
+    cycle.len().saturating_sub(1)
+}
+
+// All the words of the given length over the [0..sigma) alphabet, in
+// lexicographic order. In particular, the vertices of dBG(len, sigma).
+pub fn all_words(len: usize, sigma: u8) -> Vec<Word> {
+    let mut words = vec![Word::new()];
+    for _ in 0..len {
+        words = words
+            .iter()
+            .flat_map(|w| {
+                (0..sigma).map(move |a: Letter| {
+                    let mut w = w.clone();
+                    w.push(a);
+                    w
+                })
+            })
+            .collect();
+    }
+    words
+}